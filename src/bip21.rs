@@ -0,0 +1,215 @@
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use url::Url;
+
+const SCHEME: &str = "bitcoin";
+const SATS_PER_BTC: f64 = 100_000_000.0;
+
+/// A BIP21 payment URI like `bitcoin:bc1q...?amount=0.0015&label=Shop`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PaymentUri {
+    pub address: String,
+    /// Amount in BTC
+    pub amount: Option<f64>,
+    pub label: Option<String>,
+    pub message: Option<String>,
+    /// BOLT11 invoice to pay the same amount over Lightning instead
+    pub lightning: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Eq, thiserror::Error)]
+#[error("{msg}")]
+pub struct Bip21Error {
+    msg: String,
+}
+
+impl Bip21Error {
+    fn new(msg: &str) -> Self {
+        Self {
+            msg: format!("Invalid BIP21 URI: {msg}"),
+        }
+    }
+}
+
+impl PaymentUri {
+    /// A URI requesting `amount` BTC, rounded to whole satoshis, to `address`.
+    pub fn new(address: &str, amount: f64) -> Result<Self, Bip21Error> {
+        if address.is_empty() || !address.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return Err(Bip21Error::new(&format!(
+                "\"{address}\" is not a valid address"
+            )));
+        }
+        if !amount.is_finite() || amount < 0.0 {
+            return Err(Bip21Error::new(&format!("{amount} is not a valid amount")));
+        }
+
+        Ok(Self {
+            address: address.to_string(),
+            amount: Some((amount * SATS_PER_BTC).round() / SATS_PER_BTC),
+            label: None,
+            message: None,
+            lightning: None,
+        })
+    }
+
+    /// Whether the input looks like a BIP21 URI, without parsing it.
+    pub fn is_payment_uri(input: &str) -> bool {
+        input
+            .trim()
+            .get(..SCHEME.len() + 1)
+            .is_some_and(|scheme| scheme.eq_ignore_ascii_case("bitcoin:"))
+    }
+}
+
+impl FromStr for PaymentUri {
+    type Err = Bip21Error;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let url = Url::parse(input.trim()).map_err(|e| Bip21Error::new(&e.to_string()))?;
+        if url.scheme() != SCHEME {
+            return Err(Bip21Error::new("it doesn't start with \"bitcoin:\""));
+        }
+
+        let mut uri = PaymentUri {
+            address: url.path().to_string(),
+            amount: None,
+            label: None,
+            message: None,
+            lightning: None,
+        };
+        if uri.address.is_empty() && !url.query_pairs().any(|(key, _)| key == "lightning") {
+            return Err(Bip21Error::new("the address is missing"));
+        }
+
+        for (key, value) in url.query_pairs() {
+            match key.as_ref() {
+                "amount" => uri.amount = Some(parse_amount(&value)?),
+                "label" => uri.label = Some(value.into_owned()),
+                "message" => uri.message = Some(value.into_owned()),
+                "lightning" => uri.lightning = Some(value.into_owned()),
+                // Required parameters we don't understand make the URI unusable
+                key if key.starts_with("req-") => {
+                    return Err(Bip21Error::new(&format!(
+                        "the required parameter \"{key}\" is not supported"
+                    )))
+                }
+                _ => log::debug!("Ignoring the BIP21 parameter {key}={value}"),
+            }
+        }
+
+        Ok(uri)
+    }
+}
+
+impl Display for PaymentUri {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{SCHEME}:{}", self.address)?;
+
+        let params = [
+            ("amount", self.amount.map(format_amount)),
+            ("label", self.label.as_deref().map(percent_encode)),
+            ("message", self.message.as_deref().map(percent_encode)),
+            ("lightning", self.lightning.clone()),
+        ];
+        let mut separator = '?';
+        for (key, value) in params {
+            if let Some(value) = value {
+                write!(f, "{separator}{key}={value}")?;
+                separator = '&';
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// BIP21 amounts are plain decimal BTC, without exponents or separators.
+fn parse_amount(amount: &str) -> Result<f64, Bip21Error> {
+    let is_decimal = !amount.is_empty()
+        && amount.chars().all(|c| c.is_ascii_digit() || c == '.')
+        && amount.matches('.').count() <= 1;
+
+    amount
+        .parse()
+        .ok()
+        .filter(|_| is_decimal)
+        .ok_or(Bip21Error::new(&format!(
+            "\"{amount}\" is not a valid amount"
+        )))
+}
+
+fn format_amount(amount: f64) -> String {
+    let amount = format!("{amount:.8}");
+    amount
+        .trim_end_matches('0')
+        .trim_end_matches('.')
+        .to_string()
+}
+
+/// Percent-encodes everything but unreserved characters, so spaces become `%20` rather than `+`.
+fn percent_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{byte:02X}"),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ADDRESS: &str = "bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq";
+
+    #[test]
+    fn parses_payment_uri() {
+        let uri = PaymentUri::from_str(&format!(
+            "bitcoin:{ADDRESS}?amount=0.0015&label=Luke%20Jr&message=Donation+for+project&lightning=lnbc150u1p"
+        ))
+        .unwrap();
+
+        assert_eq!(uri.address, ADDRESS);
+        assert_eq!(uri.amount, Some(0.0015));
+        assert_eq!(uri.label.as_deref(), Some("Luke Jr"));
+        assert_eq!(uri.message.as_deref(), Some("Donation for project"));
+        assert_eq!(uri.lightning.as_deref(), Some("lnbc150u1p"));
+    }
+
+    #[test]
+    fn rejects_invalid_uris() {
+        assert!(PaymentUri::from_str(&format!("bitcoin:{ADDRESS}?amount=1e-3")).is_err());
+        assert!(PaymentUri::from_str(&format!("bitcoin:{ADDRESS}?amount=1,000")).is_err());
+        assert!(PaymentUri::from_str(&format!("bitcoin:{ADDRESS}?req-somethingnew=1")).is_err());
+        assert!(PaymentUri::from_str(&format!("litecoin:{ADDRESS}")).is_err());
+        assert!(PaymentUri::from_str("bitcoin:?amount=1").is_err());
+    }
+
+    #[test]
+    fn formats_payment_uri() {
+        let mut uri = PaymentUri::new(ADDRESS, 0.001_234_567_89).unwrap();
+        assert_eq!(
+            uri.to_string(),
+            format!("bitcoin:{ADDRESS}?amount=0.00123457")
+        );
+
+        uri.amount = Some(1.0);
+        uri.label = Some("Café & Co".to_string());
+        assert_eq!(
+            uri.to_string(),
+            format!("bitcoin:{ADDRESS}?amount=1&label=Caf%C3%A9%20%26%20Co")
+        );
+        assert_eq!(PaymentUri::from_str(&uri.to_string()), Ok(uri));
+    }
+
+    #[test]
+    fn recognizes_payment_uris() {
+        assert!(PaymentUri::is_payment_uri("BITCOIN:BC1Q..."));
+        assert!(!PaymentUri::is_payment_uri("bitcoinvert"));
+        assert!(!PaymentUri::is_payment_uri("1.5"));
+    }
+}
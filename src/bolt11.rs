@@ -0,0 +1,330 @@
+use std::fmt::Write;
+
+const BECH32_CHARSET: &str = "qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+const BECH32_GENERATOR: [u32; 5] = [
+    0x3b6a_57b2,
+    0x2650_8e6d,
+    0x1ea1_19fa,
+    0x3d42_33dd,
+    0x2a14_62b3,
+];
+const CHECKSUM_WORDS: usize = 6;
+const TIMESTAMP_WORDS: usize = 7;
+// 64 byte signature and 1 byte recovery id
+const SIGNATURE_WORDS: usize = 104;
+const MSAT_PER_BTC: u64 = 100_000_000_000;
+// BOLT11 default, when the invoice has no expiry field
+const DEFAULT_EXPIRY_SECONDS: u64 = 3600;
+
+/// The parts of a BOLT11 Lightning invoice relevant to a conversion. The signature is not
+/// verified, so the payee is only known if the invoice states it explicitly.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Invoice {
+    /// Currency prefix, e.g. `bc` for mainnet or `tb` for testnet
+    pub network: String,
+    pub amount_msat: Option<u64>,
+    /// Seconds since the UNIX epoch
+    pub timestamp: u64,
+    pub description: Option<String>,
+    /// Seconds after the timestamp
+    pub expiry: u64,
+    /// Hex encoded public key of the payee node
+    pub payee: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Eq, thiserror::Error)]
+#[error("{msg}")]
+pub struct Bolt11Error {
+    msg: String,
+}
+
+impl Bolt11Error {
+    fn new(msg: &str) -> Self {
+        Self {
+            msg: format!("Invalid Lightning invoice: {msg}"),
+        }
+    }
+}
+
+impl Invoice {
+    /// Whether the input looks like a Lightning invoice, without decoding it.
+    pub fn is_invoice(input: &str) -> bool {
+        let input = input.trim();
+        let input = Self::strip_uri_scheme(input);
+        input.len() > 2
+            && input
+                .get(..2)
+                .is_some_and(|prefix| prefix.eq_ignore_ascii_case("ln"))
+            && input.contains('1')
+    }
+
+    /// Decodes an invoice like `lnbc2500u1...`, optionally prefixed with `lightning:`.
+    pub fn decode(input: &str) -> Result<Self, Bolt11Error> {
+        let (hrp, data) = bech32_decode(Self::strip_uri_scheme(input.trim()))?;
+
+        let hrp = hrp
+            .strip_prefix("ln")
+            .ok_or(Bolt11Error::new("it doesn't start with \"ln\""))?;
+        let amount_start = hrp.find(|c: char| c.is_ascii_digit()).unwrap_or(hrp.len());
+        let (network, amount) = hrp.split_at(amount_start);
+
+        if data.len() < TIMESTAMP_WORDS + SIGNATURE_WORDS {
+            return Err(Bolt11Error::new("it's too short"));
+        }
+        let (timestamp, data) = data.split_at(TIMESTAMP_WORDS);
+        let tagged_fields = &data[..data.len() - SIGNATURE_WORDS];
+
+        let mut invoice = Invoice {
+            network: network.to_string(),
+            amount_msat: parse_amount(amount)?,
+            timestamp: words_to_int(timestamp),
+            description: None,
+            expiry: DEFAULT_EXPIRY_SECONDS,
+            payee: None,
+        };
+        invoice.read_tagged_fields(tagged_fields)?;
+
+        Ok(invoice)
+    }
+
+    fn strip_uri_scheme(input: &str) -> &str {
+        match input.split_once(':') {
+            Some((scheme, invoice)) if scheme.eq_ignore_ascii_case("lightning") => invoice,
+            _ => input,
+        }
+    }
+
+    fn read_tagged_fields(&mut self, mut fields: &[u8]) -> Result<(), Bolt11Error> {
+        while !fields.is_empty() {
+            if fields.len() < 3 {
+                return Err(Bolt11Error::new("a tagged field is truncated"));
+            }
+            let tag = BECH32_CHARSET.as_bytes()[usize::from(fields[0])];
+            let length = usize::from(fields[1]) * 32 + usize::from(fields[2]);
+            let value = fields
+                .get(3..3 + length)
+                .ok_or(Bolt11Error::new("a tagged field is truncated"))?;
+
+            match tag {
+                b'd' => {
+                    self.description = Some(
+                        String::from_utf8(words_to_bytes(value))
+                            .map_err(|_| Bolt11Error::new("the description isn't UTF-8"))?,
+                    )
+                }
+                b'x' => self.expiry = words_to_int(value),
+                // Fields of unexpected length must be skipped
+                b'n' if length == 53 => {
+                    self.payee = Some(words_to_bytes(value).iter().fold(
+                        String::new(),
+                        |mut hex, byte| {
+                            let _ = write!(hex, "{byte:02x}");
+                            hex
+                        },
+                    ))
+                }
+                _ => {}
+            }
+
+            fields = &fields[3 + length..];
+        }
+
+        Ok(())
+    }
+}
+
+/// Converts the amount of the human-readable part, e.g. `2500u`, into millisatoshis.
+fn parse_amount(amount: &str) -> Result<Option<u64>, Bolt11Error> {
+    if amount.is_empty() {
+        return Ok(None);
+    }
+
+    let (digits, multiplier) = match amount.char_indices().last() {
+        Some((index, multiplier)) if multiplier.is_ascii_alphabetic() => {
+            (&amount[..index], Some(multiplier))
+        }
+        _ => (amount, None),
+    };
+    let value: u64 = digits
+        .parse()
+        .map_err(|_| Bolt11Error::new(&format!("\"{amount}\" is not a valid amount")))?;
+
+    let msat = match multiplier {
+        None => value.checked_mul(MSAT_PER_BTC),
+        Some('m') => value.checked_mul(MSAT_PER_BTC / 1_000),
+        Some('u') => value.checked_mul(MSAT_PER_BTC / 1_000_000),
+        Some('n') => value.checked_mul(MSAT_PER_BTC / 1_000_000_000),
+        // A pico-bitcoin is a tenth of a millisatoshi
+        Some('p') if value.is_multiple_of(10) => Some(value / 10),
+        Some('p') => {
+            return Err(Bolt11Error::new(
+                "amounts in pico-bitcoin must be a multiple of 10",
+            ))
+        }
+        Some(multiplier) => {
+            return Err(Bolt11Error::new(&format!(
+                "\"{multiplier}\" is not a valid amount multiplier"
+            )))
+        }
+    };
+
+    msat.map(Some)
+        .ok_or(Bolt11Error::new(&format!("\"{amount}\" is too large")))
+}
+
+/// Splits a bech32 string into its lowercase human-readable part and its 5 bit data words,
+/// without the checksum. Unlike addresses, invoices may exceed 90 characters.
+fn bech32_decode(input: &str) -> Result<(String, Vec<u8>), Bolt11Error> {
+    if input.chars().any(|c| c.is_ascii_lowercase())
+        && input.chars().any(|c| c.is_ascii_uppercase())
+    {
+        return Err(Bolt11Error::new("it mixes upper and lower case"));
+    }
+    let input = input.to_ascii_lowercase();
+
+    let (hrp, data) = input
+        .rsplit_once('1')
+        .ok_or(Bolt11Error::new("the separator \"1\" is missing"))?;
+    let words = data
+        .chars()
+        .map(|c| BECH32_CHARSET.find(c).map(|word| word as u8))
+        .collect::<Option<Vec<u8>>>()
+        .ok_or(Bolt11Error::new("it contains invalid characters"))?;
+
+    if hrp.is_empty() || words.len() < CHECKSUM_WORDS {
+        return Err(Bolt11Error::new("it's too short"));
+    }
+    if bech32_polymod(&hrp_expand(hrp), &words) != 1 {
+        return Err(Bolt11Error::new("the checksum is wrong"));
+    }
+
+    let data = words[..words.len() - CHECKSUM_WORDS].to_vec();
+    Ok((hrp.to_string(), data))
+}
+
+fn hrp_expand(hrp: &str) -> Vec<u8> {
+    let bytes = hrp.bytes();
+    bytes
+        .clone()
+        .map(|b| b >> 5)
+        .chain([0])
+        .chain(bytes.map(|b| b & 31))
+        .collect()
+}
+
+fn bech32_polymod(hrp: &[u8], words: &[u8]) -> u32 {
+    hrp.iter().chain(words).fold(1, |checksum, &word| {
+        let top = checksum >> 25;
+        let checksum = (checksum & 0x1ff_ffff) << 5 ^ u32::from(word);
+        BECH32_GENERATOR
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| (top >> i) & 1 == 1)
+            .fold(checksum, |checksum, (_, generator)| checksum ^ generator)
+    })
+}
+
+fn words_to_int(words: &[u8]) -> u64 {
+    words
+        .iter()
+        .fold(0, |value, &word| value << 5 | u64::from(word))
+}
+
+/// Regroups 5 bit words into bytes, dropping the padding.
+fn words_to_bytes(words: &[u8]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(words.len() * 5 / 8);
+    let (mut buffer, mut bits) = (0u32, 0);
+
+    for &word in words {
+        buffer = buffer << 5 | u32::from(word);
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+            buffer &= (1 << bits) - 1;
+        }
+    }
+
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Examples from the BOLT11 specification
+    const COFFEE_INVOICE: &str = "lnbc2500u1pvjluezsp5zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zygspp5qqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqypqdq5xysxxatsyp3k7enxv4jsxqzpu9qrsgquk0rl77nj30yxdy8j9vdx85fkpmdla2087ne0xh8nhedh8w27kyke0lp53ut353s06fv3qfegext0eh0ymjpf39tuven09sam30g4vgpfna3rh";
+    const DONATION_INVOICE: &str = "lnbc1pvjluezsp5zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zygspp5qqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqypqdpl2pkx2ctnv5sxxmmwwd5kgetjypeh2ursdae8g6twvus8g6rfwvs8qun0dfjkxaq9qrsgq357wnc5r2ueh7ck6q93dj32dlqnls087fxdwk8qakdyafkq3yap9us6v52vjjsrvywa6rt52cm9r9zqt8r2t7mlcwspyetp5h2tztugp9lfyql";
+    // Testnet invoice over 10n with an explicit payee and an empty signature
+    const PIZZA_INVOICE: &str = "lntb10n1pj48ugqpp5qqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqdq52p5h57npyqnzqcn9v4eqxqzjcnp4q0n326hr8v9zprg8gsvezcch06gfaqqhde2aj730yg0durunfhv66qqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqpncgk4";
+
+    #[test]
+    fn decodes_amount_description_and_expiry() {
+        let invoice = Invoice::decode(COFFEE_INVOICE).unwrap();
+
+        assert_eq!(invoice.network, "bc");
+        assert_eq!(invoice.amount_msat, Some(250_000_000));
+        assert_eq!(invoice.timestamp, 1_496_314_658);
+        assert_eq!(invoice.description.as_deref(), Some("1 cup coffee"));
+        assert_eq!(invoice.expiry, 60);
+        assert_eq!(invoice.payee, None);
+    }
+
+    #[test]
+    fn decodes_invoice_without_amount() {
+        let invoice =
+            Invoice::decode(&format!("LIGHTNING:{}", DONATION_INVOICE.to_uppercase())).unwrap();
+
+        assert_eq!(invoice.amount_msat, None);
+        assert_eq!(invoice.expiry, DEFAULT_EXPIRY_SECONDS);
+        assert_eq!(
+            invoice.description.as_deref(),
+            Some("Please consider supporting this project")
+        );
+    }
+
+    #[test]
+    fn decodes_payee() {
+        let invoice = Invoice::decode(PIZZA_INVOICE).unwrap();
+
+        assert_eq!(invoice.network, "tb");
+        assert_eq!(invoice.amount_msat, Some(1_000));
+        assert_eq!(invoice.description.as_deref(), Some("Pizza & beer"));
+        assert_eq!(invoice.expiry, 600);
+        assert_eq!(
+            invoice.payee.as_deref(),
+            Some("03e7156ae33b0a208d0744199163177e909e80176e55d97a2f221ede0f934dd9ad")
+        );
+    }
+
+    #[test]
+    fn converts_multipliers_to_msat() {
+        assert_eq!(parse_amount("20m"), Ok(Some(2_000_000_000)));
+        assert_eq!(parse_amount("2500u"), Ok(Some(250_000_000)));
+        assert_eq!(parse_amount("10n"), Ok(Some(1_000)));
+        assert_eq!(parse_amount("10p"), Ok(Some(1)));
+        assert_eq!(parse_amount("1"), Ok(Some(MSAT_PER_BTC)));
+        assert!(parse_amount("11p").is_err());
+        assert!(parse_amount("1k").is_err());
+    }
+
+    #[test]
+    fn rejects_corrupted_invoice() {
+        let corrupted = COFFEE_INVOICE.replacen("2500u", "2600u", 1);
+
+        assert_eq!(
+            Invoice::decode(&corrupted).unwrap_err().to_string(),
+            "Invalid Lightning invoice: the checksum is wrong"
+        );
+    }
+
+    #[test]
+    fn recognizes_invoices() {
+        assert!(Invoice::is_invoice(COFFEE_INVOICE));
+        assert!(Invoice::is_invoice("lightning:lnbc1..."));
+        assert!(!Invoice::is_invoice("1.5k"));
+        assert!(!Invoice::is_invoice("ln"));
+        assert!(!Invoice::is_invoice("€100"));
+        assert!(!Invoice::is_invoice("l€1"));
+    }
+}
@@ -0,0 +1,106 @@
+use crate::currency::Currency;
+use crate::fiat_rates::exchange_rate_provider::ExchangeRates;
+
+/// Converts amounts between currencies, valuing fiat currencies with the given exchange rates.
+pub struct Converter<R: ExchangeRates> {
+    rates: R,
+}
+
+/// The result of a conversion, along with the figures it was derived from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Conversion {
+    pub amount: f64,
+    pub from: String,
+    pub to: String,
+    /// The converted amount, rounded to the decimal places of `to`
+    pub value: f64,
+    /// The converted amount, unrounded
+    pub exact_value: f64,
+    /// How many units of `to` one unit of `from` is worth
+    pub rate: f64,
+    /// The amount, expressed in BTC
+    pub value_in_btc: f64,
+}
+
+impl<R: ExchangeRates> Converter<R> {
+    pub fn new(rates: R) -> Self {
+        Self { rates }
+    }
+
+    pub fn rates(&self) -> &R {
+        &self.rates
+    }
+
+    pub fn value_in_btc(&self, amount: f64, currency: &dyn Currency) -> f64 {
+        amount * currency.btc_value(&self.rates)
+    }
+
+    pub fn convert(&self, amount: f64, from: &dyn Currency, to: &dyn Currency) -> Conversion {
+        let from_btc_value = from.btc_value(&self.rates);
+        let to_btc_value = to.btc_value(&self.rates);
+
+        let value_in_btc = amount * from_btc_value;
+        let exact_value = value_in_btc / to_btc_value;
+
+        Conversion {
+            amount,
+            from: from.to_string(),
+            to: to.to_string(),
+            value: to.round_value(exact_value),
+            exact_value,
+            rate: from_btc_value / to_btc_value,
+            value_in_btc,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::currency::btc::BitcoinUnit;
+    use crate::currency::fiat::Fiat;
+
+    struct FixedRates;
+
+    impl ExchangeRates for FixedRates {
+        fn btc_value(&self, currency: &Fiat) -> f64 {
+            match currency {
+                Fiat::USD => 1.0 / 50_000.0,
+                Fiat::EUR => 1.0 / 40_000.0,
+                _ => unimplemented!(),
+            }
+        }
+    }
+
+    #[test]
+    fn converts_bitcoin_to_fiat() {
+        let converter = Converter::new(FixedRates);
+
+        let conversion = converter.convert(12_345.0, &BitcoinUnit::SAT, &Fiat::USD);
+
+        assert_eq!(conversion.from, "SAT");
+        assert_eq!(conversion.to, "USD");
+        assert!((conversion.exact_value - 6.1725).abs() < 1e-9);
+        assert_eq!(conversion.value, 6.17);
+        assert!((conversion.rate - 0.0005).abs() < 1e-12);
+        assert!((conversion.value_in_btc - 0.000_123_45).abs() < 1e-15);
+    }
+
+    #[test]
+    fn converts_fiat_to_fiat() {
+        let converter = Converter::new(FixedRates);
+
+        let conversion = converter.convert(100.0, &Fiat::EUR, &Fiat::USD);
+
+        assert_eq!(conversion.value, 125.0);
+        assert_eq!(conversion.rate, 1.25);
+    }
+
+    #[test]
+    fn accepts_borrowed_rates() {
+        let rates = FixedRates;
+        let converter = Converter::new(&rates);
+
+        assert_eq!(converter.value_in_btc(25_000.0, &Fiat::USD), 0.5);
+    }
+}
@@ -0,0 +1,431 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::Path;
+
+use serde::Deserialize;
+use strum_macros::{Display, EnumString};
+
+use crate::fiat_rates::price_history::PriceHistory;
+
+// Leftovers smaller than this are floating point noise, not bitcoin
+const DUST_BTC: f64 = 1e-12;
+
+/// How the coins sold are matched with the coins bought.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumString, Display)]
+#[strum(ascii_case_insensitive, serialize_all = "UPPERCASE")]
+pub enum Method {
+    /// First in, first out
+    Fifo,
+    /// Last in, first out
+    Lifo,
+    /// Highest price in, first out
+    Hifo,
+    /// Every coin costs the average price of all coins held
+    Average,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Kind {
+    Buy,
+    Sell,
+}
+
+/// A buy or sell of `amount` BTC at `price` per BTC on a `YYYY-MM-DD` date.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct Transaction {
+    pub date: String,
+    #[serde(rename = "type")]
+    pub kind: Kind,
+    pub amount: f64,
+    pub price: Option<f64>,
+}
+
+/// Coins bought together, of which `amount` BTC are still held.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Lot {
+    pub date: String,
+    pub amount: f64,
+    pub price: f64,
+}
+
+/// The proceeds of the coins sold in a year, what they cost and the difference.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Realized {
+    pub proceeds: f64,
+    pub cost: f64,
+}
+
+impl Realized {
+    pub fn gain(&self) -> f64 {
+        self.proceeds - self.cost
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Gains {
+    /// Realized gains per tax year
+    pub realized: BTreeMap<i32, Realized>,
+    /// The lots still held
+    pub lots: Vec<Lot>,
+}
+
+impl Gains {
+    pub fn holdings(&self) -> f64 {
+        self.lots.iter().map(|lot| lot.amount).sum()
+    }
+
+    pub fn cost_basis(&self) -> f64 {
+        self.lots.iter().map(|lot| lot.amount * lot.price).sum()
+    }
+
+    /// What the holdings would gain if sold at `price` per BTC.
+    pub fn unrealized(&self, price: f64) -> f64 {
+        self.holdings() * price - self.cost_basis()
+    }
+}
+
+#[derive(Debug, PartialEq, thiserror::Error)]
+#[error("{msg}")]
+pub struct CostBasisError {
+    msg: String,
+}
+
+impl CostBasisError {
+    fn new(msg: String) -> Self {
+        Self { msg }
+    }
+}
+
+/// Reads the transactions from a CSV file with the columns `date,type,amount,price`, or from a
+/// YAML or JSON list with the same fields.
+pub fn read_ledger(path: &Path) -> Result<Vec<Transaction>, CostBasisError> {
+    let content = fs::read_to_string(path).map_err(|e| {
+        CostBasisError::new(format!("Unable to read ledger {}: {e}", path.display()))
+    })?;
+    let is_csv = path
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("csv"));
+
+    let transactions = if is_csv {
+        csv::ReaderBuilder::new()
+            .trim(csv::Trim::All)
+            .from_reader(content.as_bytes())
+            .deserialize()
+            .collect::<Result<_, _>>()
+            .map_err(|e| e.to_string())
+    } else {
+        crate::fiat_rates::deserialize_file(path, &content).map_err(|e| e.to_string())
+    };
+
+    transactions
+        .map_err(|e| CostBasisError::new(format!("Unable to parse ledger {}: {e}", path.display())))
+}
+
+/// Looks up the price of every transaction without one in `history`, once per date.
+pub fn fill_prices(
+    transactions: &mut [Transaction],
+    history: &dyn PriceHistory,
+) -> Result<(), CostBasisError> {
+    let mut prices: HashMap<String, Option<f64>> = HashMap::new();
+    for transaction in transactions.iter_mut().filter(|t| t.price.is_none()) {
+        let price = match prices.get(&transaction.date) {
+            Some(price) => *price,
+            None => {
+                let price = history
+                    .price_on(&transaction.date)
+                    .map_err(|e| CostBasisError::new(e.to_string()))?;
+                prices.insert(transaction.date.clone(), price);
+                price
+            }
+        };
+
+        transaction.price = Some(price.ok_or_else(|| {
+            CostBasisError::new(format!(
+                "No historical price found for the transaction on {}. Add the price per BTC to \
+                 the ledger.",
+                transaction.date
+            ))
+        })?);
+    }
+
+    Ok(())
+}
+
+/// Matches every sell with the lots bought before it, in the order of `method`. Every transaction
+/// needs a price, see [`fill_prices`].
+pub fn compute(transactions: &[Transaction], method: Method) -> Result<Gains, CostBasisError> {
+    let mut transactions = transactions.to_vec();
+    // Sorting is stable, so transactions on the same day keep their order
+    transactions.sort_by(|a, b| a.date.cmp(&b.date));
+
+    let mut gains = Gains::default();
+    for transaction in &transactions {
+        let year = year(&transaction.date)?;
+        let price = transaction.price.ok_or_else(|| {
+            CostBasisError::new(format!(
+                "The transaction on {} has no price",
+                transaction.date
+            ))
+        })?;
+        if !(transaction.amount > 0.0 && price.is_finite() && price >= 0.0) {
+            return Err(CostBasisError::new(format!(
+                "The transaction on {} needs a positive amount and price",
+                transaction.date
+            )));
+        }
+
+        match transaction.kind {
+            Kind::Buy => gains.lots.push(Lot {
+                date: transaction.date.clone(),
+                amount: transaction.amount,
+                price,
+            }),
+            Kind::Sell => {
+                let cost = sell(&mut gains.lots, transaction.amount, method).ok_or_else(|| {
+                    CostBasisError::new(format!(
+                        "The sale of {} BTC on {} is more than was held",
+                        transaction.amount, transaction.date
+                    ))
+                })?;
+                let realized = gains.realized.entry(year).or_default();
+                realized.proceeds += transaction.amount * price;
+                realized.cost += cost;
+            }
+        }
+    }
+
+    Ok(gains)
+}
+
+/// Removes `amount` BTC from the lots and returns what they cost, or `None` if there aren't enough.
+fn sell(lots: &mut Vec<Lot>, amount: f64, method: Method) -> Option<f64> {
+    let held: f64 = lots.iter().map(|lot| lot.amount).sum();
+    if amount > held + DUST_BTC {
+        return None;
+    }
+
+    let cost = match method {
+        Method::Average => {
+            // Selling the same share of every lot sells at the average price
+            let share = (amount / held).min(1.0);
+            lots.iter_mut()
+                .map(|lot| {
+                    let sold = lot.amount * share;
+                    lot.amount -= sold;
+                    sold * lot.price
+                })
+                .sum()
+        }
+        Method::Fifo | Method::Lifo | Method::Hifo => {
+            let mut order: Vec<usize> = (0..lots.len()).collect();
+            match method {
+                Method::Lifo => order.reverse(),
+                Method::Hifo => order.sort_by(|&a, &b| lots[b].price.total_cmp(&lots[a].price)),
+                _ => {}
+            }
+
+            let mut remaining = amount;
+            let mut cost = 0.0;
+            for index in order {
+                let sold = remaining.min(lots[index].amount);
+                lots[index].amount -= sold;
+                cost += sold * lots[index].price;
+                remaining -= sold;
+                if remaining <= DUST_BTC {
+                    break;
+                }
+            }
+            cost
+        }
+    };
+
+    lots.retain(|lot| lot.amount > DUST_BTC);
+    Some(cost)
+}
+
+fn year(date: &str) -> Result<i32, CostBasisError> {
+    let invalid = || CostBasisError::new(format!("\"{date}\" is not a date like 2024-04-20"));
+    let parts: Vec<&str> = date.split('-').collect();
+    let [year, month, day] = parts[..] else {
+        return Err(invalid());
+    };
+    if year.len() != 4 || month.len() != 2 || day.len() != 2 {
+        return Err(invalid());
+    }
+
+    let (Ok(year), Ok(month), Ok(day)) = (year.parse(), month.parse(), day.parse::<u32>()) else {
+        return Err(invalid());
+    };
+    let days_in_month = match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        1..=12 => 31,
+        _ => return Err(invalid()),
+    };
+    if !(1..=days_in_month).contains(&day) {
+        return Err(invalid());
+    }
+
+    Ok(year)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use super::*;
+    use crate::fiat_rates::price_history::PriceHistoryError;
+
+    fn transaction(date: &str, kind: Kind, amount: f64, price: f64) -> Transaction {
+        Transaction {
+            date: date.to_string(),
+            kind,
+            amount,
+            price: Some(price),
+        }
+    }
+
+    fn ledger() -> Vec<Transaction> {
+        vec![
+            transaction("2022-01-10", Kind::Buy, 1.0, 40_000.0),
+            transaction("2022-06-15", Kind::Buy, 1.0, 20_000.0),
+            transaction("2023-03-01", Kind::Buy, 1.0, 25_000.0),
+            transaction("2023-11-20", Kind::Sell, 1.5, 36_000.0),
+        ]
+    }
+
+    #[test]
+    fn matches_lots_by_method() {
+        let cost = |method| compute(&ledger(), method).unwrap().realized[&2023].cost;
+
+        assert_eq!(cost(Method::Fifo), 40_000.0 + 10_000.0);
+        assert_eq!(cost(Method::Lifo), 25_000.0 + 10_000.0);
+        assert_eq!(cost(Method::Hifo), 40_000.0 + 12_500.0);
+        assert!((cost(Method::Average) - 1.5 * 85_000.0 / 3.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn keeps_remaining_lots() {
+        let gains = compute(&ledger(), Method::Fifo).unwrap();
+
+        assert_eq!(
+            gains.lots,
+            vec![
+                Lot {
+                    date: "2022-06-15".to_string(),
+                    amount: 0.5,
+                    price: 20_000.0
+                },
+                Lot {
+                    date: "2023-03-01".to_string(),
+                    amount: 1.0,
+                    price: 25_000.0
+                },
+            ]
+        );
+        assert_eq!(gains.realized[&2023].gain(), 54_000.0 - 50_000.0);
+        assert_eq!(gains.unrealized(30_000.0), 45_000.0 - 35_000.0);
+    }
+
+    #[test]
+    fn sorts_by_date() {
+        let mut transactions = ledger();
+        transactions.reverse();
+
+        assert_eq!(
+            compute(&transactions, Method::Fifo),
+            compute(&ledger(), Method::Fifo)
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_transactions() {
+        let mut oversold = ledger();
+        oversold.push(transaction("2024-01-01", Kind::Sell, 2.0, 40_000.0));
+        assert!(compute(&oversold, Method::Fifo).is_err());
+
+        let mut unpriced = ledger();
+        unpriced[0].price = None;
+        assert!(compute(&unpriced, Method::Fifo).is_err());
+
+        assert!(compute(
+            &[transaction("20.4.2024", Kind::Buy, 1.0, 1.0)],
+            Method::Fifo
+        )
+        .is_err());
+
+        for date in ["2023-02-29", "2024-02-30", "2024-04-31", "2024-13-01"] {
+            assert!(compute(&[transaction(date, Kind::Buy, 1.0, 1.0)], Method::Fifo).is_err());
+        }
+        assert!(compute(
+            &[transaction("2024-02-29", Kind::Buy, 1.0, 1.0)],
+            Method::Fifo
+        )
+        .is_ok());
+
+        assert!(compute(
+            &[transaction("2024-01-01", Kind::Buy, 1.0, f64::INFINITY)],
+            Method::Fifo
+        )
+        .is_err());
+    }
+
+    struct MockHistory {
+        lookups: Cell<usize>,
+    }
+
+    impl PriceHistory for MockHistory {
+        fn price_on(&self, date: &str) -> Result<Option<f64>, PriceHistoryError> {
+            self.lookups.set(self.lookups.get() + 1);
+            Ok((date != "2009-01-03").then_some(30_000.0))
+        }
+    }
+
+    #[test]
+    fn fills_missing_prices_from_history() {
+        let mut transactions = ledger();
+        transactions[1].price = None;
+        transactions.push(Transaction {
+            price: None,
+            ..transaction("2022-06-15", Kind::Buy, 1.0, 0.0)
+        });
+        let history = MockHistory {
+            lookups: Cell::new(0),
+        };
+
+        fill_prices(&mut transactions, &history).unwrap();
+
+        assert_eq!(transactions[0].price, Some(40_000.0));
+        assert_eq!(transactions[1].price, Some(30_000.0));
+        assert_eq!(transactions[4].price, Some(30_000.0));
+        assert_eq!(history.lookups.get(), 1);
+    }
+
+    #[test]
+    fn unknown_historical_price_is_an_error() {
+        let mut transactions = vec![Transaction {
+            price: None,
+            ..transaction("2009-01-03", Kind::Buy, 50.0, 0.0)
+        }];
+        let history = MockHistory {
+            lookups: Cell::new(0),
+        };
+
+        assert_eq!(
+            fill_prices(&mut transactions, &history)
+                .unwrap_err()
+                .to_string(),
+            "No historical price found for the transaction on 2009-01-03. Add the price per BTC \
+             to the ledger."
+        );
+    }
+
+    #[test]
+    fn parses_methods() {
+        assert_eq!("fifo".parse(), Ok(Method::Fifo));
+        assert_eq!("HIFO".parse(), Ok(Method::Hifo));
+        assert_eq!(Method::Average.to_string(), "AVERAGE");
+    }
+}
@@ -0,0 +1,56 @@
+use bitcoinvert::{Currency, ExchangeRates, Fiat, FxRates};
+use colored::*;
+
+// Relative difference between the FX rate and the rate implied by the BTC prices worth a warning
+const TOLERANCE: f64 = 0.01;
+
+/// Converts `amount` between two fiat currencies. Uses the FX rate if there is one, otherwise
+/// triangulates through the BTC prices of both currencies. Reports the cross rates on stderr.
+pub fn convert(
+    amount: f64,
+    from: &Fiat,
+    to: &Fiat,
+    rates: &dyn ExchangeRates,
+    fx_rates: Option<&FxRates>,
+    verbose: bool,
+) -> f64 {
+    let implied_rate = from.btc_value(rates) / to.btc_value(rates);
+    if verbose {
+        report(&format!(
+            "1 {from} = {implied_rate:.4} {to} (implied by the BTC prices)"
+        ));
+    }
+
+    let Some(fx_rates) = fx_rates else {
+        return amount * implied_rate;
+    };
+
+    let Some(fx_rate) = fx_rates.cross_rate(from, to) else {
+        warn(&format!(
+            "No FX rate for {from}/{to}, triangulating through the BTC prices instead."
+        ));
+        return amount * implied_rate;
+    };
+
+    if verbose {
+        report(&format!("1 {from} = {fx_rate:.4} {to} (FX rate)"));
+    }
+
+    let difference = (implied_rate - fx_rate) / fx_rate;
+    if difference.abs() > TOLERANCE {
+        warn(&format!(
+            "The rate implied by the BTC prices differs from the FX rate by {:+.2}%!",
+            difference * 100.0
+        ));
+    }
+
+    amount * fx_rate
+}
+
+fn report(message: &str) {
+    eprintln!("{}", message.dimmed());
+}
+
+fn warn(message: &str) {
+    eprintln!("{}", message.yellow());
+}
@@ -1,6 +1,8 @@
 use std::str::FromStr;
 
 use crate::currency::btc::BitcoinUnit;
+use crate::currency::commodity::Commodity;
+use crate::currency::custom::CustomUnit;
 use crate::currency::fiat::Fiat;
 use crate::currency::Currency;
 
@@ -14,6 +16,12 @@ pub struct CurrencyParseError {
 
 impl Currencies {
     pub fn parse(input: &str) -> Result<Box<dyn Currency>, CurrencyParseError> {
+        if let Some(hint) = Self::hint(input) {
+            return Err(CurrencyParseError {
+                msg: format!("\"{input}\" is ambiguous. {hint}"),
+            });
+        }
+
         if let Ok(btc) = BitcoinUnit::from_str(input) {
             return Ok(Box::new(btc));
         }
@@ -22,10 +30,54 @@ impl Currencies {
             return Ok(Box::new(fiat));
         }
 
+        if let Ok(commodity) = Commodity::from_str(input) {
+            return Ok(Box::new(commodity));
+        }
+
         Err(CurrencyParseError {
             msg: format!("Unable to parse the following currency: {}", input),
         })
     }
+
+    /// Explains how to write units that are ambiguous, e.g. "Msat" could be a milli- or a
+    /// mega-satoshi. Other than the case of the first letter, units are case-insensitive.
+    pub fn hint(input: &str) -> Option<&'static str> {
+        let rest = input.strip_prefix('M')?;
+        let is_sat = ["sat", "sats"]
+            .iter()
+            .any(|sat| rest.eq_ignore_ascii_case(sat));
+
+        if is_sat && rest.chars().any(|c| c.is_ascii_lowercase()) {
+            Some("Use MSAT for milli-satoshis, or 1M SAT for a million satoshis.")
+        } else {
+            None
+        }
+    }
+
+    /// Parses the definition of a custom unit of the form `<amount> <currency>`, e.g. `4.50 CHF`.
+    pub fn parse_custom_unit(
+        name: &str,
+        definition: &str,
+    ) -> Result<CustomUnit, CurrencyParseError> {
+        let error = || CurrencyParseError {
+            msg: format!(
+                "\"{definition}\" is not a valid definition of {name}, expected e.g. \"4.50 CHF\""
+            ),
+        };
+
+        let (amount, unit) = definition
+            .trim()
+            .split_once(char::is_whitespace)
+            .ok_or_else(error)?;
+        let amount: f64 = amount.parse().map_err(|_| error())?;
+        let unit = Self::parse(unit.trim()).map_err(|_| error())?;
+
+        Ok(CustomUnit {
+            name: name.to_string(),
+            amount,
+            unit,
+        })
+    }
 }
 
 #[cfg(test)]
@@ -54,6 +106,49 @@ mod tests {
         assert_eq!(currency_uppercase.to_string(), "BTC");
     }
 
+    #[test]
+    fn should_return_denomination_for_alias() {
+        assert_eq!(Currencies::parse("sats").unwrap().to_string(), "SAT");
+        assert_eq!(Currencies::parse("bit").unwrap().to_string(), "BITS");
+        assert_eq!(Currencies::parse("uBTC").unwrap().to_string(), "µBTC");
+        assert_eq!(Currencies::parse("µbtc").unwrap().to_string(), "µBTC");
+        assert_eq!(Currencies::parse("cBTC").unwrap().to_string(), "CBTC");
+        assert_eq!(Currencies::parse("kSat").unwrap().to_string(), "KSAT");
+        assert_eq!(Currencies::parse("millisats").unwrap().to_string(), "MSAT");
+    }
+
+    #[test]
+    fn ambiguous_mega_or_milli_satoshi_is_rejected() {
+        assert_eq!(Currencies::parse("msat").unwrap().to_string(), "MSAT");
+        assert_eq!(Currencies::parse("MSAT").unwrap().to_string(), "MSAT");
+        assert!(Currencies::parse("Msat").is_err());
+        assert!(Currencies::parse("MSats").is_err());
+        assert!(Currencies::hint("Msat").is_some());
+        assert!(Currencies::hint("msat").is_none());
+    }
+
+    #[test]
+    fn should_return_correct_commodity() {
+        assert_eq!(Currencies::parse("xau").unwrap().to_string(), "XAU");
+        assert_eq!(Currencies::parse("oz-gold").unwrap().to_string(), "XAU");
+        assert_eq!(Currencies::parse("g-gold").unwrap().to_string(), "g-gold");
+    }
+
+    #[test]
+    fn should_parse_custom_unit() {
+        let coffee = Currencies::parse_custom_unit("coffee", "4.50 CHF").unwrap();
+        assert_eq!(coffee.name, "coffee");
+        assert_eq!(coffee.amount, 4.5);
+        assert_eq!(coffee.unit.to_string(), "CHF");
+
+        let beer = Currencies::parse_custom_unit("beer", " 6000  sat ").unwrap();
+        assert_eq!(beer.unit.to_string(), "SAT");
+
+        assert!(Currencies::parse_custom_unit("salary", "7500").is_err());
+        assert!(Currencies::parse_custom_unit("salary", "a lot EUR").is_err());
+        assert!(Currencies::parse_custom_unit("salary", "7500 XYZ").is_err());
+    }
+
     #[test]
     fn incorrect_use_should_return_error() {
         let currency_empty_string = Currencies::parse("");
@@ -1,39 +1,79 @@
 use serde::{Deserialize, Serialize};
-use strum_macros::{Display, EnumString};
+use strum_macros::{Display, EnumIter, EnumString};
 
 use crate::currency::Currency;
+use crate::fiat_rates::exchange_rate_provider::ExchangeRates;
 
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, EnumString, Display)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, EnumString, EnumIter, Display)]
 #[strum(ascii_case_insensitive, serialize_all = "UPPERCASE")]
 pub enum BitcoinUnit {
-    BTC,  // bitcoin
+    #[strum(to_string = "BTC", serialize = "bitcoin", serialize = "bitcoins")]
+    BTC, // bitcoin
+    DBTC, // deci-bitcoin
+    CBTC, // centi-bitcoin
     MBTC, // milli-bitcoin
-    BITS, // μBTC, micro-bitcoin
-    SAT,  // satoshi
+    #[strum(to_string = "KSAT", serialize = "ksats")]
+    KSAT, // kilo-satoshi
+    // BIP-176
+    #[strum(to_string = "BITS", serialize = "bit")]
+    BITS, // micro-bitcoin
+    #[strum(to_string = "µBTC", serialize = "μBTC", serialize = "uBTC")]
+    UBTC, // micro-bitcoin, like BITS
+    #[strum(
+        to_string = "SAT",
+        serialize = "sats",
+        serialize = "satoshi",
+        serialize = "satoshis",
+        serialize = "base-units"
+    )]
+    SAT, // satoshi
+    #[strum(
+        to_string = "MSAT",
+        serialize = "msats",
+        serialize = "millisat",
+        serialize = "millisats"
+    )]
     MSAT, // milli-satoshi
 }
 
-#[typetag::serde]
-impl Currency for BitcoinUnit {
-    fn btc_value(&self) -> f64 {
+impl BitcoinUnit {
+    pub fn btc_value(&self) -> f64 {
         match &self {
             BitcoinUnit::BTC => 1.0,
+            BitcoinUnit::DBTC => 0.1,
+            BitcoinUnit::CBTC => 0.01,
             BitcoinUnit::MBTC => 0.001,
-            BitcoinUnit::BITS => 0.000_001,
+            BitcoinUnit::KSAT => 0.000_01,
+            BitcoinUnit::BITS | BitcoinUnit::UBTC => 0.000_001,
             BitcoinUnit::SAT => 0.000_000_01,
             BitcoinUnit::MSAT => 0.000_000_000_01,
         }
     }
+}
+
+#[typetag::serde]
+impl Currency for BitcoinUnit {
+    // Bitcoin denominations have fixed values, independent of any exchange rate.
+    fn btc_value(&self, _rates: &dyn ExchangeRates) -> f64 {
+        BitcoinUnit::btc_value(self)
+    }
 
     fn decimal_places(&self) -> u8 {
         match self {
             BitcoinUnit::BTC => 8,
+            BitcoinUnit::DBTC => 7,
+            BitcoinUnit::CBTC => 6,
             BitcoinUnit::MBTC => 5,
-            BitcoinUnit::BITS => 2,
+            BitcoinUnit::KSAT => 3,
+            BitcoinUnit::BITS | BitcoinUnit::UBTC => 2,
             BitcoinUnit::SAT => 3,
             BitcoinUnit::MSAT => 0,
         }
     }
+
+    fn as_bitcoin_unit(&self) -> Option<&BitcoinUnit> {
+        Some(self)
+    }
 }
 
 #[cfg(test)]
@@ -44,8 +84,12 @@ mod tests {
     fn arb_btc_unit() -> impl Strategy<Value = BitcoinUnit> {
         prop_oneof![
             Just(BitcoinUnit::BTC),
+            Just(BitcoinUnit::DBTC),
+            Just(BitcoinUnit::CBTC),
             Just(BitcoinUnit::MBTC),
+            Just(BitcoinUnit::KSAT),
             Just(BitcoinUnit::BITS),
+            Just(BitcoinUnit::UBTC),
             Just(BitcoinUnit::SAT),
             Just(BitcoinUnit::MSAT),
         ]
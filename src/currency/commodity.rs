@@ -0,0 +1,115 @@
+use serde::{Deserialize, Serialize};
+use strum_macros::{Display, EnumIter, EnumString};
+
+use crate::currency::Currency;
+use crate::fiat_rates::exchange_rate_provider::ExchangeRates;
+
+const GRAMS_PER_TROY_OUNCE: f64 = 31.103_476_8;
+
+/// Precious metals, identified by their ISO 4217 codes.
+#[derive(
+    Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash, EnumString, EnumIter, Display,
+)]
+#[strum(ascii_case_insensitive)]
+pub enum Metal {
+    XAU, // gold
+    XAG, // silver
+    XPT, // platinum
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, EnumString, EnumIter, Display)]
+#[strum(ascii_case_insensitive)]
+pub enum Commodity {
+    #[strum(to_string = "XAU", serialize = "oz-gold")]
+    XAU, // troy ounce of gold
+    #[strum(to_string = "XAG", serialize = "oz-silver")]
+    XAG, // troy ounce of silver
+    #[strum(to_string = "XPT", serialize = "oz-platinum")]
+    XPT, // troy ounce of platinum
+    #[strum(to_string = "g-gold", serialize = "g-XAU")]
+    GramGold,
+    #[strum(to_string = "g-silver", serialize = "g-XAG")]
+    GramSilver,
+    #[strum(to_string = "g-platinum", serialize = "g-XPT")]
+    GramPlatinum,
+}
+
+impl Commodity {
+    pub fn metal(&self) -> Metal {
+        match self {
+            Commodity::XAU | Commodity::GramGold => Metal::XAU,
+            Commodity::XAG | Commodity::GramSilver => Metal::XAG,
+            Commodity::XPT | Commodity::GramPlatinum => Metal::XPT,
+        }
+    }
+
+    pub fn troy_ounces(&self) -> f64 {
+        match self {
+            Commodity::XAU | Commodity::XAG | Commodity::XPT => 1.0,
+            Commodity::GramGold | Commodity::GramSilver | Commodity::GramPlatinum => {
+                1.0 / GRAMS_PER_TROY_OUNCE
+            }
+        }
+    }
+}
+
+#[typetag::serde]
+impl Currency for Commodity {
+    fn btc_value(&self, rates: &dyn ExchangeRates) -> f64 {
+        let metal_btc_value = rates
+            .metal_btc_value(&self.metal())
+            .unwrap_or_else(|e| panic!("{e}!"));
+
+        metal_btc_value * self.troy_ounces()
+    }
+
+    fn decimal_places(&self) -> u8 {
+        match self {
+            Commodity::XAU | Commodity::XAG | Commodity::XPT => 4,
+            Commodity::GramGold | Commodity::GramSilver | Commodity::GramPlatinum => 2,
+        }
+    }
+
+    fn as_commodity(&self) -> Option<&Commodity> {
+        Some(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::currency::fiat::Fiat;
+    use crate::fiat_rates::MissingRateError;
+    use std::str::FromStr;
+
+    struct GoldAt(f64);
+
+    impl ExchangeRates for GoldAt {
+        fn btc_value(&self, _currency: &Fiat) -> f64 {
+            unimplemented!()
+        }
+
+        fn metal_btc_value(&self, _metal: &Metal) -> Result<f64, MissingRateError> {
+            Ok(self.0)
+        }
+    }
+
+    #[test]
+    fn parses_ounces_and_grams() {
+        assert_eq!(Commodity::from_str("xau"), Ok(Commodity::XAU));
+        assert_eq!(Commodity::from_str("oz-gold"), Ok(Commodity::XAU));
+        assert_eq!(Commodity::from_str("g-silver"), Ok(Commodity::GramSilver));
+        assert_eq!(Commodity::from_str("g-XPT"), Ok(Commodity::GramPlatinum));
+        assert_eq!(Commodity::GramGold.to_string(), "g-gold");
+    }
+
+    #[test]
+    fn gram_is_fraction_of_troy_ounce() {
+        let rates = GoldAt(0.04);
+
+        assert_eq!(Commodity::XAU.btc_value(&rates), 0.04);
+        assert!(
+            (Commodity::GramGold.btc_value(&rates) * GRAMS_PER_TROY_OUNCE - 0.04).abs() < 1e-12
+        );
+    }
+}
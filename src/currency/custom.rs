@@ -0,0 +1,57 @@
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+
+use crate::currency::Currency;
+use crate::fiat_rates::exchange_rate_provider::ExchangeRates;
+
+/// A unit defined by the user as an amount of another currency, e.g. a coffee worth 4.50 CHF.
+#[derive(Serialize, Deserialize)]
+pub struct CustomUnit {
+    pub name: String,
+    pub amount: f64,
+    pub unit: Box<dyn Currency>,
+}
+
+impl Display for CustomUnit {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+#[typetag::serde]
+impl Currency for CustomUnit {
+    fn btc_value(&self, rates: &dyn ExchangeRates) -> f64 {
+        self.amount * self.unit.btc_value(rates)
+    }
+
+    fn decimal_places(&self) -> u8 {
+        2
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::currency::btc::BitcoinUnit;
+    use crate::currency::fiat::Fiat;
+    use std::collections::HashMap;
+
+    #[test]
+    fn is_worth_its_amount_of_the_underlying_unit() {
+        let rates = HashMap::from([(Fiat::CHF, 50_000.0)]);
+        let coffee = CustomUnit {
+            name: "coffee".to_string(),
+            amount: 4.5,
+            unit: Box::new(Fiat::CHF),
+        };
+        let beer = CustomUnit {
+            name: "beer".to_string(),
+            amount: 6_000.0,
+            unit: Box::new(BitcoinUnit::SAT),
+        };
+
+        assert!((coffee.btc_value(&rates) - 0.000_09).abs() < 1e-15);
+        assert!((beer.btc_value(&rates) - 0.000_06).abs() < 1e-15);
+        assert_eq!(coffee.to_string(), "coffee");
+    }
+}
@@ -1,22 +1,12 @@
-use crate::fiat_rates::blockchain_info_consumer;
 use serde::{Deserialize, Serialize};
-use std::sync::{LazyLock, Mutex};
-use strum_macros::{Display, EnumString};
+use strum_macros::{Display, EnumIter, EnumString};
 
 use crate::currency::Currency;
-use crate::fiat_rates::exchange_rate_provider::ExchangeRateProvider;
+use crate::fiat_rates::exchange_rate_provider::ExchangeRates;
 
-// Static to have an easy way of caching the exchange rates.
-static EXCHANGE_RATE_PROVIDER: LazyLock<
-    Mutex<ExchangeRateProvider<blockchain_info_consumer::ApiConsumer>>,
-> = LazyLock::new(|| {
-    Mutex::new(ExchangeRateProvider {
-        data_source: blockchain_info_consumer::ApiConsumer,
-        data: None,
-    })
-});
-
-#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, EnumString, Display)]
+#[derive(
+    Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash, EnumString, EnumIter, Display,
+)]
 #[strum(ascii_case_insensitive, serialize_all = "UPPERCASE")]
 pub enum Fiat {
     ARS,
@@ -33,12 +23,18 @@ pub enum Fiat {
     GHS,
     HKD,
     HUF,
+    IDR,
+    ILS,
     INR,
     ISK,
     JPY,
     KRW,
+    MXN,
+    MYR,
     NGN,
+    NOK,
     NZD,
+    PHP,
     PLN,
     RON,
     RUB,
@@ -48,15 +44,13 @@ pub enum Fiat {
     TRY,
     TWD,
     USD,
+    ZAR,
 }
 
 #[typetag::serde]
 impl Currency for Fiat {
-    fn btc_value(&self) -> f64 {
-        EXCHANGE_RATE_PROVIDER
-            .lock()
-            .expect("Failed to lock EXCHANGE_RATE_PROVIDER")
-            .btc_value(self)
+    fn btc_value(&self, rates: &dyn ExchangeRates) -> f64 {
+        rates.btc_value(self)
     }
 
     fn decimal_places(&self) -> u8 {
@@ -72,9 +66,14 @@ impl Currency for Fiat {
             | Fiat::GBP
             | Fiat::GHS
             | Fiat::HKD
+            | Fiat::ILS
             | Fiat::INR
+            | Fiat::MXN
+            | Fiat::MYR
             | Fiat::NGN
+            | Fiat::NOK
             | Fiat::NZD
+            | Fiat::PHP
             | Fiat::PLN
             | Fiat::RON
             | Fiat::RUB
@@ -83,34 +82,49 @@ impl Currency for Fiat {
             | Fiat::THB
             | Fiat::TRY
             | Fiat::TWD
-            | Fiat::USD => 2,
-            Fiat::ARS | Fiat::HUF | Fiat::JPY | Fiat::CLP | Fiat::ISK | Fiat::KRW => 0,
+            | Fiat::USD
+            | Fiat::ZAR => 2,
+            Fiat::ARS | Fiat::HUF | Fiat::IDR | Fiat::JPY | Fiat::CLP | Fiat::ISK | Fiat::KRW => 0,
         }
     }
+
+    fn as_fiat(&self) -> Option<&Fiat> {
+        Some(self)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::time::Instant;
+    use crate::fiat_rates::exchange_rate_provider::{
+        ExchangeRateApiConsumer, ExchangeRateProvider,
+    };
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Mutex;
+
+    static FETCH_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+    struct MockApiConsumer;
+
+    impl ExchangeRateApiConsumer for MockApiConsumer {
+        fn fetch_api(&self) -> HashMap<Fiat, f64> {
+            FETCH_COUNT.fetch_add(1, Ordering::SeqCst);
+            HashMap::from([(Fiat::USD, 50_000.0), (Fiat::EUR, 45_000.0)])
+        }
+    }
 
     #[test]
     fn test_exchange_rate_caching() {
-        let start = Instant::now();
+        let rates = Mutex::new(ExchangeRateProvider::new(MockApiConsumer));
 
         // First call fetches from the API.
-        let btc_value = Fiat::USD.btc_value();
-        let elapsed_first_call = start.elapsed();
-        assert!(btc_value > 0.0);
+        let btc_value = Fiat::USD.btc_value(&rates);
+        assert!((btc_value - 1.0 / 50_000.0).abs() < f64::EPSILON);
 
-        // Second call should use cached data and be much faster.
-        let start2 = Instant::now();
-        let btc_value = Fiat::EUR.btc_value();
-        let elapsed_second_call = start2.elapsed();
-        assert!(btc_value > 0.0);
-        assert!(
-            elapsed_second_call < elapsed_first_call / 10,
-            "Second call ({elapsed_second_call:?}) should be much faster than first ({elapsed_first_call:?})"
-        );
+        // Second call uses the cached data.
+        let btc_value = Fiat::EUR.btc_value(&rates);
+        assert!((btc_value - 1.0 / 45_000.0).abs() < f64::EPSILON);
+        assert_eq!(FETCH_COUNT.load(Ordering::SeqCst), 1);
     }
 }
@@ -1,14 +1,30 @@
 use std::fmt::Display;
 
+use crate::currency::btc::BitcoinUnit;
+use crate::currency::commodity::Commodity;
+use crate::currency::fiat::Fiat;
+use crate::fiat_rates::exchange_rate_provider::ExchangeRates;
+
 pub mod btc;
+pub mod commodity;
+pub mod custom;
 pub mod fiat;
 
 #[typetag::serde()]
 pub trait Currency: Display {
-    fn btc_value(&self) -> f64;
+    fn btc_value(&self, rates: &dyn ExchangeRates) -> f64;
     fn decimal_places(&self) -> u8;
     fn round_value(&self, value: f64) -> f64 {
         let factor = 10_f64.powi(self.decimal_places().into());
         (value * factor).round() / factor
     }
+    fn as_fiat(&self) -> Option<&Fiat> {
+        None
+    }
+    fn as_bitcoin_unit(&self) -> Option<&BitcoinUnit> {
+        None
+    }
+    fn as_commodity(&self) -> Option<&Commodity> {
+        None
+    }
 }
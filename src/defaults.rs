@@ -1,18 +1,28 @@
 use home_config::HomeConfig;
 use serde::{Deserialize, Serialize};
+use serde_yml::Value;
+use std::collections::BTreeMap;
 use std::error::Error;
+use std::path::PathBuf;
 
-use crate::currency::btc::BitcoinUnit;
-use crate::currency::fiat::Fiat;
-use crate::Currency;
+use bitcoinvert::{BitcoinUnit, Currencies, Currency, CustomUnit, Fiat};
 
 const DEFAULTS_FILE: &str = "defaults.yaml";
+const CUSTOM_UNIT_TAG: &str = "CustomUnit";
 
 #[derive(Serialize, Deserialize)]
 pub struct Defaults {
     amount: f64,
     input_currency: Box<dyn Currency>,
     output_currencies: Vec<Box<dyn Currency>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    rates_file: Option<PathBuf>,
+    /// Bitcoin Core RPC URL to estimate fee rates with
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    node_url: Option<String>,
+    /// Units defined as an amount of another currency, e.g. `coffee: 4.50 CHF`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    custom_units: BTreeMap<String, String>,
 }
 
 impl Defaults {
@@ -28,6 +38,32 @@ impl Defaults {
         Ok(Self::retrieve()?.output_currencies)
     }
 
+    /// Relative paths are resolved against the directory of the defaults file.
+    pub fn get_default_rates_file() -> Result<Option<PathBuf>, Box<dyn Error>> {
+        let rates_file = Self::retrieve()?.rates_file;
+        let config = HomeConfig::with_config_dir(env!("CARGO_PKG_NAME"), DEFAULTS_FILE);
+
+        Ok(rates_file.map(|path| match config.path().parent() {
+            Some(config_dir) => config_dir.join(path),
+            None => path,
+        }))
+    }
+
+    pub fn get_default_node_url() -> Result<Option<String>, Box<dyn Error>> {
+        Ok(Self::retrieve()?.node_url)
+    }
+
+    /// Looks up a custom unit by its name, ignoring the case.
+    pub fn get_custom_unit(name: &str) -> Result<Option<CustomUnit>, Box<dyn Error>> {
+        let custom_units = Self::retrieve()?.custom_units;
+
+        custom_units
+            .iter()
+            .find(|(unit, _)| unit.eq_ignore_ascii_case(name))
+            .map(|(unit, definition)| Ok(Currencies::parse_custom_unit(unit, definition)?))
+            .transpose()
+    }
+
     pub fn retrieve() -> Result<Defaults, Box<dyn Error>> {
         let config = HomeConfig::with_config_dir(env!("CARGO_PKG_NAME"), DEFAULTS_FILE);
 
@@ -49,7 +85,7 @@ impl Defaults {
     }
 
     fn load_defaults(config: &HomeConfig) -> Result<Defaults, Box<dyn Error>> {
-        let defaults: Defaults = serde_yml::from_str(&config.read_to_string()?)?;
+        let defaults = Self::parse(&config.read_to_string()?)?;
         log::debug!(
             "Reading contents of file {} --> input amount: {}, input currency: {}, output currencies: [{}]",
             config.path().display(),
@@ -66,6 +102,50 @@ impl Defaults {
         Ok(defaults)
     }
 
+    fn parse(yaml: &str) -> Result<Defaults, Box<dyn Error>> {
+        let mut defaults: Value = serde_yml::from_str(yaml)?;
+        Self::resolve_custom_units(&mut defaults)?;
+
+        Ok(serde_yml::from_value(defaults)?)
+    }
+
+    /// Replaces references to custom units (`CustomUnit: coffee`) in the input and output
+    /// currencies by their full definition, so they can be deserialized like any other currency.
+    fn resolve_custom_units(defaults: &mut Value) -> Result<(), Box<dyn Error>> {
+        let custom_units: BTreeMap<String, String> = match defaults.get("custom_units") {
+            Some(custom_units) => serde_yml::from_value(custom_units.clone())?,
+            None => BTreeMap::new(),
+        };
+
+        let mut currencies: Vec<&mut Value> = Vec::new();
+        if let Some(mapping) = defaults.as_mapping_mut() {
+            for (key, value) in mapping.iter_mut() {
+                match key.as_str() {
+                    "input_currency" => currencies.push(value),
+                    "output_currencies" => {
+                        currencies.extend(value.as_sequence_mut().into_iter().flatten())
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        for currency in currencies {
+            let Some(name) = currency.get(CUSTOM_UNIT_TAG).and_then(Value::as_str) else {
+                continue;
+            };
+            let definition = custom_units.get(name).ok_or(format!(
+                "Custom unit \"{name}\" is not defined in custom_units"
+            ))?;
+            let custom_unit: Box<dyn Currency> =
+                Box::new(Currencies::parse_custom_unit(name, definition)?);
+
+            *currency = serde_yml::to_value(custom_unit)?;
+        }
+
+        Ok(())
+    }
+
     fn setup(config: &HomeConfig) -> Result<(), Box<dyn Error>> {
         config
             .save_yaml(Self::load_defaults_template())
@@ -85,6 +165,60 @@ impl Defaults {
                 Box::new(Fiat::EUR),
                 Box::new(Fiat::GBP),
             ],
+            rates_file: None,
+            node_url: None,
+            custom_units: BTreeMap::new(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn custom_units_are_resolved() {
+        let defaults = Defaults::parse(
+            "
+amount: 1.0
+input_currency:
+  CustomUnit: coffee
+output_currencies:
+  - BitcoinUnit: SAT
+  - CustomUnit: beer
+custom_units:
+  coffee: 4.50 CHF
+  beer: 6000 SAT
+",
+        )
+        .unwrap();
+
+        assert_eq!(defaults.input_currency.to_string(), "coffee");
+        assert_eq!(
+            defaults
+                .output_currencies
+                .iter()
+                .map(|c| c.to_string())
+                .collect::<Vec<_>>(),
+            vec!["SAT", "beer"]
+        );
+    }
+
+    #[test]
+    fn undefined_custom_unit_is_an_error() {
+        let result = Defaults::parse(
+            "
+amount: 1.0
+input_currency:
+  BitcoinUnit: SAT
+output_currencies:
+  - CustomUnit: coffee
+",
+        );
+
+        assert_eq!(
+            result.err().unwrap().to_string(),
+            "Custom unit \"coffee\" is not defined in custom_units"
+        );
+    }
+}
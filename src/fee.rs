@@ -0,0 +1,93 @@
+use std::error::Error;
+
+use bitcoinvert::fee_estimate::NodeFeeEstimator;
+use bitcoinvert::tx_size;
+use bitcoinvert::{check_quoted, BitcoinUnit, Currency, ExchangeRates};
+
+use crate::cli_input::{CliInput, FeeArgs};
+use crate::defaults::Defaults;
+use crate::print;
+
+/// Prints the fee of a transaction in every requested or configured output currency.
+pub fn run(args: &FeeArgs, rates: &dyn ExchangeRates) -> Result<(), Box<dyn Error>> {
+    let vsize = match args.vbytes {
+        Some(vbytes) => vbytes,
+        None => {
+            let output_script = args.output_script.unwrap_or(args.script);
+            let inputs = vec![args.script; args.inputs.unwrap_or(1)];
+            let outputs = vec![output_script; args.outputs.unwrap_or(2)];
+            let vsize = tx_size::estimate_vsize(&inputs, &outputs);
+
+            println!(
+                "Estimated size of {} {} input(s) and {} {} output(s): {vsize} vB",
+                inputs.len(),
+                args.script,
+                outputs.len(),
+                output_script
+            );
+            vsize
+        }
+    };
+
+    let fee_rate = fee_rate(args)?;
+    // Fees are paid in whole satoshis
+    let fee_in_sat = (fee_rate * vsize as f64).ceil();
+    println!("Fee at {fee_rate} sat/vB: {fee_in_sat} SAT\n");
+
+    print::multi_line(
+        fee_in_sat * BitcoinUnit::SAT.btc_value(),
+        &output_currencies(&args.currencies, rates)?,
+        rates,
+        false,
+        None,
+    );
+
+    Ok(())
+}
+
+/// The given fee rate, or else the one estimated by the given or configured node.
+fn fee_rate(args: &FeeArgs) -> Result<f64, Box<dyn Error>> {
+    if let Some(fee_rate) = args.fee_rate {
+        return Ok(fee_rate);
+    }
+
+    let node = match &args.node {
+        Some(node) => node.clone(),
+        None => Defaults::get_default_node_url()?.ok_or(
+            "Pass a fee rate with --fee-rate, or a node to estimate it with --node or node_url in the defaults",
+        )?,
+    };
+
+    let fee_rate = NodeFeeEstimator::new(&node).estimate_smart_fee(args.target)?;
+    // Bitcoin Core reports sat/vB with three decimals
+    let fee_rate = (fee_rate * 1000.0).round() / 1000.0;
+    println!(
+        "Estimated fee rate to confirm within {} blocks: {fee_rate} sat/vB",
+        args.target
+    );
+
+    Ok(fee_rate)
+}
+
+/// The given currencies, or else the configured output currencies, all of which must have a rate.
+pub(crate) fn output_currencies(
+    currencies: &[String],
+    rates: &dyn ExchangeRates,
+) -> Result<Vec<Box<dyn Currency>>, Box<dyn Error>> {
+    let currencies = if currencies.is_empty() {
+        Defaults::get_default_output_currencies()?
+    } else {
+        currencies
+            .iter()
+            .map(|currency| {
+                CliInput::parse_currency(currency)
+                    .ok_or_else(|| format!("\"{currency}\" is not a valid currency!"))
+            })
+            .collect::<Result<_, _>>()?
+    };
+
+    for currency in &currencies {
+        check_quoted(&**currency, rates)?;
+    }
+    Ok(currencies)
+}
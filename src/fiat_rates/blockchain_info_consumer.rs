@@ -4,7 +4,7 @@ use std::collections::HashMap;
 use crate::currency::fiat::Fiat;
 use crate::fiat_rates::exchange_rate_provider::ExchangeRateApiConsumer;
 
-const SOURCE_API: &str = "https://blockchain.info/ticker";
+pub const SOURCE_API: &str = "https://blockchain.info/ticker";
 
 pub struct ApiConsumer;
 
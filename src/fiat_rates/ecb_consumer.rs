@@ -0,0 +1,185 @@
+use regex::Regex;
+use std::collections::HashMap;
+use std::fs;
+use std::str::FromStr;
+
+use crate::currency::fiat::Fiat;
+use crate::fiat_rates::exchange_rate_provider::ExchangeRateApiConsumer;
+use crate::fiat_rates::fx_rates::FxRates;
+
+pub const ECB_DAILY_URL: &str = "https://www.ecb.europa.eu/stats/eurofxref/eurofxref-daily.xml";
+
+/// Derives the price of 1 BTC in every currency the ECB publishes a reference rate for, from the
+/// price in a single `anchor` currency. Prices of currencies the ECB doesn't publish are taken
+/// from the wrapped data source as they are.
+pub struct EcbConsumer<T: ExchangeRateApiConsumer> {
+    btc_prices: T,
+    anchor: Fiat,
+    fx_rates: FxRates,
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("{msg}")]
+pub struct EcbError {
+    msg: String,
+}
+
+impl<T: ExchangeRateApiConsumer> EcbConsumer<T> {
+    /// Loads the reference rates once from `source`, either an http(s) URL or a file path.
+    pub fn new(source: &str, anchor: Fiat, btc_prices: T) -> Result<Self, EcbError> {
+        let fx_rates = parse_reference_rates(&load(source)?)?;
+
+        if fx_rates.cross_rate(&anchor, &fx_rates.base).is_none() {
+            return Err(EcbError {
+                msg: format!("The ECB reference rates don't include {anchor}"),
+            });
+        }
+
+        Ok(Self {
+            btc_prices,
+            anchor,
+            fx_rates,
+        })
+    }
+
+    pub fn fx_rates(&self) -> &FxRates {
+        &self.fx_rates
+    }
+}
+
+impl<T: ExchangeRateApiConsumer> ExchangeRateApiConsumer for EcbConsumer<T> {
+    fn fetch_api(&self) -> HashMap<Fiat, f64> {
+        let mut prices = self.btc_prices.fetch_api();
+        let anchor_price = *prices.get(&self.anchor).unwrap_or_else(|| {
+            panic!(
+                "No BTC price in {} to derive the other currencies from!",
+                self.anchor
+            )
+        });
+
+        let currencies = std::iter::once(&self.fx_rates.base).chain(self.fx_rates.rates.keys());
+        for currency in currencies {
+            if let Some(rate) = self.fx_rates.cross_rate(&self.anchor, currency) {
+                prices.insert(currency.clone(), anchor_price * rate);
+            }
+        }
+
+        prices
+    }
+}
+
+fn load(source: &str) -> Result<String, EcbError> {
+    if source.starts_with("http://") || source.starts_with("https://") {
+        log::debug!("Request ECB reference rates from {source}");
+        reqwest::blocking::get(source)
+            .and_then(|response| response.error_for_status())
+            .and_then(|response| response.text())
+            .map_err(|e| EcbError {
+                msg: format!("Unable to request ECB reference rates from {source}: {e}"),
+            })
+    } else {
+        fs::read_to_string(source).map_err(|e| EcbError {
+            msg: format!("Unable to read ECB reference rates file {source}: {e}"),
+        })
+    }
+}
+
+/// Parses the ECB's daily reference rates XML, which quotes every currency against the euro.
+/// Currencies that aren't supported are skipped.
+pub fn parse_reference_rates(xml: &str) -> Result<FxRates, EcbError> {
+    let cube = Regex::new(r#"<Cube\s+currency=['"](\w+)['"]\s+rate=['"]([^'"]+)['"]"#).unwrap();
+
+    let mut rates = HashMap::new();
+    for captures in cube.captures_iter(xml) {
+        let (symbol, rate) = (&captures[1], &captures[2]);
+        let rate: f64 = rate.parse().map_err(|_| EcbError {
+            msg: format!("\"{rate}\" is not a valid reference rate for {symbol}"),
+        })?;
+
+        match Fiat::from_str(symbol) {
+            Ok(fiat) => {
+                rates.insert(fiat, rate);
+            }
+            Err(_) => log::debug!("Ignoring unsupported currency {symbol} in ECB reference rates"),
+        }
+    }
+
+    if rates.is_empty() {
+        return Err(EcbError {
+            msg: "No reference rates found in the ECB XML".to_string(),
+        });
+    }
+
+    Ok(FxRates {
+        base: Fiat::EUR,
+        rates,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIXTURE: &str = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/fixtures/eurofxref-daily.xml"
+    );
+
+    struct UsdOnly;
+
+    impl ExchangeRateApiConsumer for UsdOnly {
+        fn fetch_api(&self) -> HashMap<Fiat, f64> {
+            HashMap::from([(Fiat::USD, 65_000.0), (Fiat::ARS, 60_000_000.0)])
+        }
+    }
+
+    fn assert_approx(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 1e-6,
+            "{actual} is not {expected}"
+        );
+    }
+
+    #[test]
+    fn parses_reference_rates() {
+        let fx_rates = parse_reference_rates(&fs::read_to_string(FIXTURE).unwrap()).unwrap();
+
+        assert_eq!(fx_rates.base, Fiat::EUR);
+        assert_eq!(fx_rates.rates[&Fiat::USD], 1.0842);
+        assert_eq!(fx_rates.rates[&Fiat::MXN], 18.4713);
+        assert_eq!(fx_rates.rates.len(), 29);
+    }
+
+    #[test]
+    fn xml_without_rates_is_an_error() {
+        let result = parse_reference_rates("<gesmes:Envelope></gesmes:Envelope>");
+
+        assert_eq!(
+            result.err().unwrap().to_string(),
+            "No reference rates found in the ECB XML"
+        );
+    }
+
+    #[test]
+    fn derives_prices_from_anchor() {
+        let consumer = EcbConsumer::new(FIXTURE, Fiat::USD, UsdOnly).unwrap();
+        let prices = consumer.fetch_api();
+
+        assert_approx(prices[&Fiat::USD], 65_000.0);
+        assert_approx(prices[&Fiat::EUR], 65_000.0 / 1.0842);
+        assert_approx(prices[&Fiat::MXN], 65_000.0 / 1.0842 * 18.4713);
+        // Not published by the ECB
+        assert_approx(prices[&Fiat::ARS], 60_000_000.0);
+    }
+
+    #[test]
+    fn missing_file_is_an_error() {
+        let result = EcbConsumer::new("does/not/exist.xml", Fiat::USD, UsdOnly);
+
+        assert!(result
+            .err()
+            .unwrap()
+            .to_string()
+            .starts_with("Unable to read ECB reference rates file does/not/exist.xml"));
+    }
+}
@@ -1,20 +1,104 @@
+use crate::currency::commodity::Metal;
 use crate::currency::fiat::Fiat;
+use crate::fiat_rates::MissingRateError;
 use std::collections::HashMap;
+use std::sync::{Mutex, PoisonError};
 
 pub trait ExchangeRateApiConsumer {
     fn fetch_api(&self) -> HashMap<Fiat, f64>;
 }
 
+impl<T: ExchangeRateApiConsumer + ?Sized> ExchangeRateApiConsumer for Box<T> {
+    fn fetch_api(&self) -> HashMap<Fiat, f64> {
+        (**self).fetch_api()
+    }
+}
+
+/// Source of the exchange rates that fiat currencies are converted with.
+pub trait ExchangeRates {
+    /// The value of one unit of `currency`, expressed in BTC.
+    fn btc_value(&self, currency: &Fiat) -> f64;
+
+    /// Whether there is a rate for `currency` at all.
+    fn is_quoted(&self, _currency: &Fiat) -> bool {
+        true
+    }
+
+    /// The value of one troy ounce of `metal`, expressed in BTC, if its price is available.
+    fn metal_btc_value(&self, metal: &Metal) -> Result<f64, MissingRateError> {
+        Err(MissingRateError::metal(format!(
+            "No price available for {metal}"
+        )))
+    }
+
+    /// Drops cached rates, if any, so the next lookup gets up-to-date ones.
+    fn clear_cache(&self) {}
+}
+
+impl<R: ExchangeRates + ?Sized> ExchangeRates for &R {
+    fn btc_value(&self, currency: &Fiat) -> f64 {
+        (**self).btc_value(currency)
+    }
+
+    fn is_quoted(&self, currency: &Fiat) -> bool {
+        (**self).is_quoted(currency)
+    }
+
+    fn metal_btc_value(&self, metal: &Metal) -> Result<f64, MissingRateError> {
+        (**self).metal_btc_value(metal)
+    }
+
+    fn clear_cache(&self) {
+        (**self).clear_cache()
+    }
+}
+
+/// Fixed rates, mapping every fiat currency to the price of 1 BTC.
+impl ExchangeRates for HashMap<Fiat, f64> {
+    fn btc_value(&self, currency: &Fiat) -> f64 {
+        1.0 / self
+            .get(currency)
+            .unwrap_or_else(|| panic!("No exchange rate available for {currency}!"))
+    }
+
+    fn is_quoted(&self, currency: &Fiat) -> bool {
+        self.contains_key(currency)
+    }
+}
+
 pub struct ExchangeRateProvider<T: ExchangeRateApiConsumer> {
     pub data_source: T,
     pub data: Option<HashMap<Fiat, f64>>,
 }
 
 impl<T: ExchangeRateApiConsumer> ExchangeRateProvider<T> {
+    pub fn new(data_source: T) -> Self {
+        Self {
+            data_source,
+            data: None,
+        }
+    }
+
     pub fn btc_value(&mut self, currency: &Fiat) -> f64 {
         self.fetch();
 
-        1.0 / self.data.as_ref().unwrap().get(currency).unwrap()
+        1.0 / self
+            .data
+            .as_ref()
+            .unwrap()
+            .get(currency)
+            .unwrap_or_else(|| panic!("No exchange rate available for {currency}!"))
+    }
+
+    pub fn is_quoted(&mut self, currency: &Fiat) -> bool {
+        self.fetch();
+
+        self.data.as_ref().unwrap().contains_key(currency)
+    }
+
+    /// Drops the cached rates, so the next lookup fetches them from the data source again.
+    pub fn clear_cache(&mut self) {
+        self.data = None;
     }
 
     fn fetch(&mut self) {
@@ -24,6 +108,27 @@ impl<T: ExchangeRateApiConsumer> ExchangeRateProvider<T> {
     }
 }
 
+// A fetch that panicked leaves no rates behind, so the provider is still usable afterwards
+impl<T: ExchangeRateApiConsumer> ExchangeRates for Mutex<ExchangeRateProvider<T>> {
+    fn btc_value(&self, currency: &Fiat) -> f64 {
+        self.lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .btc_value(currency)
+    }
+
+    fn is_quoted(&self, currency: &Fiat) -> bool {
+        self.lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .is_quoted(currency)
+    }
+
+    fn clear_cache(&self) {
+        self.lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clear_cache()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -92,13 +197,33 @@ mod tests {
     }
 
     #[test]
-    #[should_panic(expected = "called `Option::unwrap()` on a `None` value")]
+    fn data_is_fetched_again_after_clearing_cache() {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let mut provider = mock_provider_with_fetch(&COUNTER);
+
+        provider.btc_value(&Fiat::USD);
+        provider.clear_cache();
+        assert!(provider.data.is_none());
+
+        provider.btc_value(&Fiat::USD);
+        assert_eq!(COUNTER.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "No exchange rate available for USD!")]
     fn missing_currency_panics() {
         let rates = HashMap::new();
         let mut provider = mock_provider_with_data(rates);
         provider.btc_value(&Fiat::USD);
     }
 
+    #[test]
+    #[should_panic(expected = "No exchange rate available for MXN!")]
+    fn missing_fixed_rate_panics() {
+        let rates = HashMap::from([(Fiat::USD, 50_000.0)]);
+        ExchangeRates::btc_value(&rates, &Fiat::MXN);
+    }
+
     #[test]
     fn zero_rate_produces_infinity() {
         let mut rates = HashMap::new();
@@ -109,6 +234,30 @@ mod tests {
         assert!(btc_value.is_infinite());
     }
 
+    #[test]
+    fn shared_provider_caches_data() {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let provider = Mutex::new(mock_provider_with_fetch(&COUNTER));
+        let rates: &dyn ExchangeRates = &provider;
+
+        assert!((rates.btc_value(&Fiat::USD) - 1.0 / 50_000.0).abs() < f64::EPSILON);
+        assert!((rates.btc_value(&Fiat::EUR) - 1.0 / 45_000.0).abs() < f64::EPSILON);
+        assert_eq!(COUNTER.load(Ordering::SeqCst), 1);
+
+        rates.clear_cache();
+        rates.btc_value(&Fiat::USD);
+        assert_eq!(COUNTER.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn fixed_rates_return_inverse_of_price() {
+        let rates = HashMap::from([(Fiat::USD, 50_000.0)]);
+
+        assert!(
+            (ExchangeRates::btc_value(&rates, &Fiat::USD) - 1.0 / 50_000.0).abs() < f64::EPSILON
+        );
+    }
+
     #[test]
     fn multiple_currencies_return_correct_values() {
         let mut rates = HashMap::new();
@@ -0,0 +1,133 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::currency::fiat::Fiat;
+use crate::fiat_rates::deserialize_file;
+
+/// Exchange rates between fiat currencies, e.g. central bank reference rates, quoted against a
+/// base currency.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FxRates {
+    pub base: Fiat,
+    /// How many units of every currency one unit of `base` buys
+    pub rates: HashMap<Fiat, f64>,
+}
+
+#[derive(Deserialize, Debug)]
+struct FxRatesFile {
+    base: String,
+    rates: HashMap<String, f64>,
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("{msg}")]
+pub struct FxRatesError {
+    msg: String,
+}
+
+impl FxRates {
+    /// Reads a JSON or YAML file of the shape `{"base": "EUR", "rates": {"USD": 1.08, ...}}`.
+    /// Currencies that aren't supported are skipped.
+    pub fn from_file(path: &Path) -> Result<Self, FxRatesError> {
+        let content = fs::read_to_string(path).map_err(|e| FxRatesError {
+            msg: format!("Unable to read FX rates file {}: {e}", path.display()),
+        })?;
+        let file: FxRatesFile = deserialize_file(path, &content).map_err(|e| FxRatesError {
+            msg: format!("Unable to parse FX rates file {}: {e}", path.display()),
+        })?;
+
+        let base = Fiat::from_str(&file.base).map_err(|_| FxRatesError {
+            msg: format!("\"{}\" is not a supported base currency", file.base),
+        })?;
+        if let Some((symbol, rate)) = file
+            .rates
+            .iter()
+            .find(|(_, rate)| !rate.is_finite() || **rate <= 0.0)
+        {
+            return Err(FxRatesError {
+                msg: format!(
+                    "The FX rate of {symbol} in {} must be positive, not {rate}",
+                    path.display()
+                ),
+            });
+        }
+        let rates = file
+            .rates
+            .into_iter()
+            .filter_map(|(symbol, rate)| Fiat::from_str(&symbol).ok().map(|fiat| (fiat, rate)))
+            .collect();
+
+        Ok(Self { base, rates })
+    }
+
+    /// How many units of `to` one unit of `from` buys, if both currencies are quoted.
+    pub fn cross_rate(&self, from: &Fiat, to: &Fiat) -> Option<f64> {
+        Some(self.per_base(to)? / self.per_base(from)?)
+    }
+
+    fn per_base(&self, currency: &Fiat) -> Option<f64> {
+        if *currency == self.base {
+            Some(1.0)
+        } else {
+            self.rates.get(currency).copied()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fx_rates() -> FxRates {
+        FxRates {
+            base: Fiat::EUR,
+            rates: HashMap::from([(Fiat::USD, 1.25), (Fiat::CHF, 0.95)]),
+        }
+    }
+
+    #[test]
+    fn cross_rate_from_and_to_base() {
+        assert_eq!(fx_rates().cross_rate(&Fiat::EUR, &Fiat::USD), Some(1.25));
+        assert_eq!(fx_rates().cross_rate(&Fiat::USD, &Fiat::EUR), Some(0.8));
+        assert_eq!(fx_rates().cross_rate(&Fiat::EUR, &Fiat::EUR), Some(1.0));
+    }
+
+    #[test]
+    fn cross_rate_between_quoted_currencies() {
+        let cross_rate = fx_rates().cross_rate(&Fiat::USD, &Fiat::CHF).unwrap();
+        assert!((cross_rate - 0.76).abs() < 1e-12);
+    }
+
+    #[test]
+    fn cross_rate_of_unquoted_currency_is_none() {
+        assert_eq!(fx_rates().cross_rate(&Fiat::JPY, &Fiat::EUR), None);
+    }
+
+    #[test]
+    fn reads_fx_rates_file() {
+        let path = Path::new(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/fixtures/fx_rates.yaml"
+        ));
+        let fx_rates = FxRates::from_file(path).unwrap();
+
+        assert_eq!(fx_rates.base, Fiat::EUR);
+        assert_eq!(fx_rates.rates[&Fiat::CHF], 0.9412);
+        // XDR is not supported
+        assert_eq!(fx_rates.rates.len(), 3);
+    }
+
+    #[test]
+    fn rates_must_be_positive() {
+        for rate in ["0", "-1.08", ".nan"] {
+            let path = std::env::temp_dir().join(format!("bitcoinvert_fx_rates_{rate}.yaml"));
+            fs::write(&path, format!("base: EUR\nrates:\n  USD: {rate}\n")).unwrap();
+
+            let error = FxRates::from_file(&path).unwrap_err().to_string();
+            assert!(error.starts_with("The FX rate of USD in "), "{error}");
+        }
+    }
+}
@@ -0,0 +1,275 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::{Mutex, PoisonError};
+
+use strum::IntoEnumIterator;
+
+use crate::currency::commodity::Metal;
+use crate::currency::fiat::Fiat;
+use crate::fiat_rates::deserialize_file;
+use crate::fiat_rates::exchange_rate_provider::ExchangeRates;
+use crate::fiat_rates::MissingRateError;
+
+const SOURCE_API: &str = "https://api.gold-api.com/price";
+
+/// Source of precious metal prices, in USD per troy ounce.
+pub trait MetalPriceConsumer {
+    fn fetch_prices(&self) -> Result<HashMap<Metal, f64>, MetalPricesError>;
+}
+
+impl<T: MetalPriceConsumer + ?Sized> MetalPriceConsumer for Box<T> {
+    fn fetch_prices(&self) -> Result<HashMap<Metal, f64>, MetalPricesError> {
+        (**self).fetch_prices()
+    }
+}
+
+pub struct MetalApiConsumer;
+
+#[derive(Deserialize, Debug)]
+struct Quote {
+    price: f64,
+}
+
+impl MetalPriceConsumer for MetalApiConsumer {
+    fn fetch_prices(&self) -> Result<HashMap<Metal, f64>, MetalPricesError> {
+        Metal::iter()
+            .map(|metal| {
+                let url = format!("{SOURCE_API}/{metal}");
+                log::debug!("Request metal price from {url}");
+                let quote: Quote = reqwest::blocking::get(&url)
+                    .and_then(|response| response.error_for_status())
+                    .and_then(|response| response.json())
+                    .map_err(|e| MetalPricesError {
+                        msg: format!("Unable to request the price of {metal} from {url}: {e}"),
+                    })?;
+
+                Ok((metal, quote.price))
+            })
+            .collect()
+    }
+}
+
+/// Reads the metal prices from a JSON or YAML file mapping every metal to its price in USD per
+/// troy ounce, e.g. `{"XAU": 2650.3, "XAG": 31.25}`.
+pub struct MetalPricesFileConsumer {
+    path: PathBuf,
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("{msg}")]
+pub struct MetalPricesError {
+    msg: String,
+}
+
+impl MetalPricesFileConsumer {
+    /// Reports an unreadable file right away, not only once a commodity gets converted.
+    pub fn new(path: impl Into<PathBuf>) -> Result<Self, MetalPricesError> {
+        let consumer = Self { path: path.into() };
+        consumer.read_prices()?;
+
+        Ok(consumer)
+    }
+
+    fn read_prices(&self) -> Result<HashMap<Metal, f64>, MetalPricesError> {
+        let content = fs::read_to_string(&self.path).map_err(|e| MetalPricesError {
+            msg: format!(
+                "Unable to read metal prices file {}: {e}",
+                self.path.display()
+            ),
+        })?;
+        let prices: HashMap<String, f64> =
+            deserialize_file(&self.path, &content).map_err(|e| MetalPricesError {
+                msg: format!(
+                    "Unable to parse metal prices file {}: {e}",
+                    self.path.display()
+                ),
+            })?;
+
+        Ok(prices
+            .into_iter()
+            .filter_map(|(symbol, price)| Metal::from_str(&symbol).ok().map(|m| (m, price)))
+            .collect())
+    }
+}
+
+impl MetalPriceConsumer for MetalPricesFileConsumer {
+    fn fetch_prices(&self) -> Result<HashMap<Metal, f64>, MetalPricesError> {
+        self.read_prices()
+    }
+}
+
+/// Adds precious metal prices to the wrapped rates. The prices are only fetched once a metal is
+/// actually converted, and then cached until the cache is cleared.
+pub struct MetalPrices<T: MetalPriceConsumer, R: ExchangeRates> {
+    data_source: T,
+    prices: Mutex<Option<HashMap<Metal, f64>>>,
+    rates: R,
+}
+
+impl<T: MetalPriceConsumer, R: ExchangeRates> MetalPrices<T, R> {
+    pub fn new(data_source: T, rates: R) -> Self {
+        Self {
+            data_source,
+            prices: Mutex::new(None),
+            rates,
+        }
+    }
+}
+
+impl<T: MetalPriceConsumer, R: ExchangeRates> ExchangeRates for MetalPrices<T, R> {
+    fn btc_value(&self, currency: &Fiat) -> f64 {
+        self.rates.btc_value(currency)
+    }
+
+    fn is_quoted(&self, currency: &Fiat) -> bool {
+        self.rates.is_quoted(currency)
+    }
+
+    fn metal_btc_value(&self, metal: &Metal) -> Result<f64, MissingRateError> {
+        let mut prices = self.prices.lock().unwrap_or_else(PoisonError::into_inner);
+        // A failed fetch leaves no prices behind, so they are fetched again next time
+        if prices.is_none() {
+            let fetched = self
+                .data_source
+                .fetch_prices()
+                .map_err(|e| MissingRateError::metal(e.to_string()))?;
+            *prices = Some(fetched);
+        }
+        let price = prices
+            .as_ref()
+            .and_then(|prices| prices.get(metal))
+            .copied()
+            .ok_or_else(|| MissingRateError::metal(format!("No price available for {metal}")))?;
+
+        // Metal prices are quoted in USD
+        if !self.rates.is_quoted(&Fiat::USD) {
+            return Err(MissingRateError::fiat(&Fiat::USD));
+        }
+        Ok(price * self.rates.btc_value(&Fiat::USD))
+    }
+
+    fn clear_cache(&self) {
+        *self.prices.lock().unwrap_or_else(PoisonError::into_inner) = None;
+        self.rates.clear_cache();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    struct MockConsumer {
+        fetch_count: AtomicUsize,
+        // The number of fetches that fail before one succeeds
+        failures: usize,
+    }
+
+    impl MetalPriceConsumer for MockConsumer {
+        fn fetch_prices(&self) -> Result<HashMap<Metal, f64>, MetalPricesError> {
+            if self.fetch_count.fetch_add(1, Ordering::SeqCst) < self.failures {
+                return Err(MetalPricesError {
+                    msg: "Unable to request the price of XAU".to_string(),
+                });
+            }
+            Ok(HashMap::from([(Metal::XAU, 2_600.0)]))
+        }
+    }
+
+    fn metal_prices_failing(failures: usize) -> MetalPrices<MockConsumer, HashMap<Fiat, f64>> {
+        MetalPrices::new(
+            MockConsumer {
+                fetch_count: AtomicUsize::new(0),
+                failures,
+            },
+            HashMap::from([(Fiat::USD, 65_000.0)]),
+        )
+    }
+
+    fn metal_prices() -> MetalPrices<MockConsumer, HashMap<Fiat, f64>> {
+        metal_prices_failing(0)
+    }
+
+    #[test]
+    fn metal_is_valued_through_usd() {
+        let prices = metal_prices();
+
+        assert!((prices.metal_btc_value(&Metal::XAU).unwrap() - 0.04).abs() < 1e-12);
+    }
+
+    #[test]
+    fn prices_are_cached_until_cleared() {
+        let prices = metal_prices();
+        prices.metal_btc_value(&Metal::XAU).unwrap();
+        prices.metal_btc_value(&Metal::XAU).unwrap();
+        assert_eq!(prices.data_source.fetch_count.load(Ordering::SeqCst), 1);
+
+        prices.clear_cache();
+        prices.metal_btc_value(&Metal::XAU).unwrap();
+        assert_eq!(prices.data_source.fetch_count.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn failed_fetch_is_an_error_and_retried() {
+        let prices = metal_prices_failing(1);
+
+        assert_eq!(
+            prices.metal_btc_value(&Metal::XAU).unwrap_err().to_string(),
+            "Unable to request the price of XAU"
+        );
+        assert!(prices.metal_btc_value(&Metal::XAU).is_ok());
+    }
+
+    #[test]
+    fn metal_needs_usd_rate() {
+        let prices = MetalPrices::new(
+            MockConsumer {
+                fetch_count: AtomicUsize::new(0),
+                failures: 0,
+            },
+            HashMap::from([(Fiat::EUR, 60_000.0)]),
+        );
+
+        let error = prices.metal_btc_value(&Metal::XAU).unwrap_err();
+        assert_eq!(error.to_string(), "No exchange rate available for USD");
+        assert!(error.is_fiat());
+    }
+
+    #[test]
+    fn fiat_is_not_fetched() {
+        let prices = metal_prices();
+        prices.btc_value(&Fiat::USD);
+
+        assert_eq!(prices.data_source.fetch_count.load(Ordering::SeqCst), 0);
+    }
+
+    #[test]
+    fn missing_metal_is_an_error() {
+        assert_eq!(
+            metal_prices()
+                .metal_btc_value(&Metal::XAG)
+                .unwrap_err()
+                .to_string(),
+            "No price available for XAG"
+        );
+    }
+
+    #[test]
+    fn reads_metal_prices_file() {
+        let path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/fixtures/metal_prices.yaml"
+        );
+        let prices = MetalPricesFileConsumer::new(path)
+            .unwrap()
+            .fetch_prices()
+            .unwrap();
+
+        assert_eq!(prices.len(), 3);
+        assert_eq!(prices[&Metal::XAU], 2_600.0);
+    }
+}
@@ -1,2 +1,104 @@
+use serde::de::DeserializeOwned;
+use std::error::Error;
+use std::path::Path;
+
+use crate::currency::fiat::Fiat;
+use crate::currency::Currency;
+use crate::fiat_rates::exchange_rate_provider::ExchangeRates;
+
 pub mod blockchain_info_consumer;
+pub mod ecb_consumer;
 pub mod exchange_rate_provider;
+pub mod fx_rates;
+pub mod metal_prices;
+pub mod price_history;
+pub mod rate_overrides;
+pub mod rates_file_consumer;
+
+#[derive(Debug, PartialEq, Eq, thiserror::Error)]
+#[error("{msg}")]
+pub struct MissingRateError {
+    msg: String,
+    fiat: bool,
+}
+
+impl MissingRateError {
+    pub(crate) fn fiat(fiat: &Fiat) -> Self {
+        Self {
+            msg: format!("No exchange rate available for {fiat}"),
+            fiat: true,
+        }
+    }
+
+    pub(crate) fn metal(msg: String) -> Self {
+        Self { msg, fiat: false }
+    }
+
+    /// Whether an exchange rate of a fiat currency is missing, rather than a metal price.
+    pub fn is_fiat(&self) -> bool {
+        self.fiat
+    }
+}
+
+/// Fails for a fiat currency without an exchange rate or a metal without a price, which can't be
+/// converted.
+pub fn check_quoted(
+    currency: &dyn Currency,
+    rates: &dyn ExchangeRates,
+) -> Result<(), MissingRateError> {
+    if let Some(commodity) = currency.as_commodity() {
+        return rates.metal_btc_value(&commodity.metal()).map(|_| ());
+    }
+
+    match currency.as_fiat() {
+        Some(fiat) if !rates.is_quoted(fiat) => Err(MissingRateError::fiat(fiat)),
+        _ => Ok(()),
+    }
+}
+
+/// Deserializes the contents of a JSON file, or a YAML file for any other extension.
+// serde_yml only deserializes types without borrowed data
+pub(crate) fn deserialize_file<T: DeserializeOwned + 'static>(
+    path: &Path,
+    content: &str,
+) -> Result<T, Box<dyn Error>> {
+    let is_json = path
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("json"));
+
+    if is_json {
+        Ok(serde_json::from_str(content)?)
+    } else {
+        Ok(serde_yml::from_str(content)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::currency::btc::BitcoinUnit;
+    use crate::currency::commodity::Commodity;
+
+    #[test]
+    fn only_unquoted_fiat_is_missing_a_rate() {
+        let rates = HashMap::from([(Fiat::USD, 50_000.0)]);
+
+        assert_eq!(check_quoted(&Fiat::USD, &rates), Ok(()));
+        assert_eq!(check_quoted(&BitcoinUnit::SAT, &rates), Ok(()));
+        assert_eq!(
+            check_quoted(&Fiat::MXN, &rates).unwrap_err().to_string(),
+            "No exchange rate available for MXN"
+        );
+    }
+
+    #[test]
+    fn metal_without_price_is_missing_a_rate() {
+        let rates = HashMap::from([(Fiat::USD, 50_000.0)]);
+        let error = check_quoted(&Commodity::GramGold, &rates).unwrap_err();
+
+        assert_eq!(error.to_string(), "No price available for XAU");
+        assert!(!error.is_fiat());
+    }
+}
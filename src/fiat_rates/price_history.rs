@@ -0,0 +1,182 @@
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use crate::fiat_rates::deserialize_file;
+
+pub const SOURCE_API: &str = "https://api.blockchain.info/charts/market-price";
+
+/// Source of the price of 1 BTC on past days.
+pub trait PriceHistory {
+    /// The price of 1 BTC on a `YYYY-MM-DD` date, or `None` if it isn't known.
+    fn price_on(&self, date: &str) -> Result<Option<f64>, PriceHistoryError>;
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("{msg}")]
+pub struct PriceHistoryError {
+    msg: String,
+}
+
+/// Requests the daily market price in USD from the blockchain.info charts API.
+pub struct PriceHistoryApiConsumer {
+    url: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct Chart {
+    values: Vec<ChartPoint>,
+}
+
+#[derive(Deserialize, Debug)]
+struct ChartPoint {
+    y: f64,
+}
+
+impl PriceHistoryApiConsumer {
+    pub fn new(url: &str) -> Self {
+        Self {
+            url: url.to_string(),
+        }
+    }
+}
+
+impl Default for PriceHistoryApiConsumer {
+    fn default() -> Self {
+        Self::new(SOURCE_API)
+    }
+}
+
+impl PriceHistory for PriceHistoryApiConsumer {
+    fn price_on(&self, date: &str) -> Result<Option<f64>, PriceHistoryError> {
+        let url = format!("{}?start={date}&timespan=1days&format=json", self.url);
+        log::debug!("Request historical price from {url}");
+
+        let chart: Chart = reqwest::blocking::get(&url)
+            .and_then(|response| response.error_for_status())
+            .and_then(|response| response.json())
+            .map_err(|e| PriceHistoryError {
+                msg: format!(
+                    "Unable to request the price on {date} from {}: {e}",
+                    self.url
+                ),
+            })?;
+
+        // Before bitcoin had a market, the chart lists a price of 0
+        Ok(chart
+            .values
+            .first()
+            .map(|point| point.y)
+            .filter(|price| *price > 0.0))
+    }
+}
+
+/// Reads the daily prices from a CSV file with the columns `date,price`, or from a JSON or YAML
+/// file mapping every date to its price, e.g. `{"2024-04-20": 64000}`.
+pub struct PriceHistoryFileConsumer {
+    prices: BTreeMap<String, f64>,
+}
+
+#[derive(Deserialize, Debug)]
+struct DailyPrice {
+    date: String,
+    price: f64,
+}
+
+impl PriceHistoryFileConsumer {
+    /// Loads every price at once, as a ledger usually looks up many dates.
+    pub fn new(path: &Path) -> Result<Self, PriceHistoryError> {
+        let content = fs::read_to_string(path).map_err(|e| PriceHistoryError {
+            msg: format!("Unable to read prices file {}: {e}", path.display()),
+        })?;
+        let is_csv = path
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("csv"));
+
+        let prices = if is_csv {
+            csv::ReaderBuilder::new()
+                .trim(csv::Trim::All)
+                .from_reader(content.as_bytes())
+                .deserialize()
+                .map(|price| price.map(|price: DailyPrice| (price.date, price.price)))
+                .collect::<Result<_, _>>()
+                .map_err(|e| e.to_string())
+        } else {
+            deserialize_file(path, &content).map_err(|e| e.to_string())
+        };
+
+        let prices = prices.map_err(|e| PriceHistoryError {
+            msg: format!("Unable to parse prices file {}: {e}", path.display()),
+        })?;
+        log::debug!("Read historical prices from {}", path.display());
+
+        Ok(Self { prices })
+    }
+}
+
+impl PriceHistory for PriceHistoryFileConsumer {
+    fn price_on(&self, date: &str) -> Result<Option<f64>, PriceHistoryError> {
+        Ok(self.prices.get(date).copied())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_server;
+
+    fn mock_api(body: &'static str) -> String {
+        mock_server::serve_once(body, |request| {
+            assert!(request.url().starts_with("/?start=2024-04-20&"));
+        })
+    }
+
+    #[test]
+    fn requests_price_of_the_day() {
+        let url = mock_api(r#"{"status":"ok","values":[{"x":1713571200,"y":64940.53}]}"#);
+
+        let price = PriceHistoryApiConsumer::new(&url).price_on("2024-04-20");
+
+        assert_eq!(price.unwrap(), Some(64_940.53));
+    }
+
+    #[test]
+    fn day_without_price_is_unknown() {
+        let url = mock_api(r#"{"status":"ok","values":[]}"#);
+
+        let price = PriceHistoryApiConsumer::new(&url).price_on("2024-04-20");
+
+        assert_eq!(price.unwrap(), None);
+    }
+
+    #[test]
+    fn reads_prices_file() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/prices.csv");
+        let prices = PriceHistoryFileConsumer::new(Path::new(path)).unwrap();
+
+        assert_eq!(prices.price_on("2022-06-15").unwrap(), Some(20_000.0));
+        assert_eq!(prices.price_on("2022-06-16").unwrap(), None);
+    }
+
+    #[test]
+    fn reads_json_price_map() {
+        let path = std::env::temp_dir().join("bitcoinvert_prices.json");
+        fs::write(&path, r#"{"2024-04-20": 64000.5}"#).unwrap();
+
+        let prices = PriceHistoryFileConsumer::new(&path).unwrap();
+
+        assert_eq!(prices.price_on("2024-04-20").unwrap(), Some(64_000.5));
+    }
+
+    #[test]
+    fn missing_file_is_an_error() {
+        let result = PriceHistoryFileConsumer::new(Path::new("does/not/exist.csv"));
+
+        assert!(result
+            .err()
+            .unwrap()
+            .to_string()
+            .starts_with("Unable to read prices file does/not/exist.csv"));
+    }
+}
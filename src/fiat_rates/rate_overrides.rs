@@ -0,0 +1,71 @@
+use std::collections::HashMap;
+
+use crate::currency::commodity::Metal;
+use crate::currency::fiat::Fiat;
+use crate::fiat_rates::exchange_rate_provider::ExchangeRates;
+use crate::fiat_rates::MissingRateError;
+
+/// Pins the price of 1 BTC for some fiat currencies, e.g. to convert at an agreed price. All
+/// other currencies are looked up in the wrapped rates, which aren't touched at all as long as
+/// only pinned currencies are converted.
+pub struct RateOverrides<R: ExchangeRates> {
+    prices: HashMap<Fiat, f64>,
+    rates: R,
+}
+
+impl<R: ExchangeRates> RateOverrides<R> {
+    pub fn new(prices: HashMap<Fiat, f64>, rates: R) -> Self {
+        Self { prices, rates }
+    }
+}
+
+impl<R: ExchangeRates> ExchangeRates for RateOverrides<R> {
+    fn btc_value(&self, currency: &Fiat) -> f64 {
+        match self.prices.get(currency) {
+            Some(price) => 1.0 / price,
+            None => self.rates.btc_value(currency),
+        }
+    }
+
+    fn is_quoted(&self, currency: &Fiat) -> bool {
+        self.prices.contains_key(currency) || self.rates.is_quoted(currency)
+    }
+
+    fn metal_btc_value(&self, metal: &Metal) -> Result<f64, MissingRateError> {
+        self.rates.metal_btc_value(metal)
+    }
+
+    fn clear_cache(&self) {
+        self.rates.clear_cache();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct UnusedRates;
+
+    impl ExchangeRates for UnusedRates {
+        fn btc_value(&self, _currency: &Fiat) -> f64 {
+            panic!("Rates should not have been looked up");
+        }
+    }
+
+    #[test]
+    fn pinned_price_is_used() {
+        let rates = RateOverrides::new(HashMap::from([(Fiat::USD, 64_250.5)]), UnusedRates);
+
+        assert!((rates.btc_value(&Fiat::USD) - 1.0 / 64_250.5).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn other_currencies_fall_back_to_wrapped_rates() {
+        let rates = RateOverrides::new(
+            HashMap::from([(Fiat::USD, 64_250.5)]),
+            HashMap::from([(Fiat::USD, 50_000.0), (Fiat::EUR, 45_000.0)]),
+        );
+
+        assert!((rates.btc_value(&Fiat::EUR) - 1.0 / 45_000.0).abs() < f64::EPSILON);
+    }
+}
@@ -0,0 +1,138 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use crate::currency::fiat::Fiat;
+use crate::fiat_rates::deserialize_file;
+use crate::fiat_rates::exchange_rate_provider::ExchangeRateApiConsumer;
+
+/// Reads the exchange rates from a local JSON or YAML file instead of requesting them from an API.
+///
+/// The file either has the shape of the blockchain.info ticker, or simply maps every currency to
+/// the price of 1 BTC, e.g. `{"USD": 65000, "EUR": 59000}`.
+pub struct RatesFileConsumer {
+    path: PathBuf,
+}
+
+#[derive(Deserialize, Debug)]
+struct Ticker {
+    last: f64,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+enum Rate {
+    Price(f64),
+    Ticker(Ticker),
+}
+
+impl Rate {
+    fn price(&self) -> f64 {
+        match self {
+            Rate::Price(price) => *price,
+            Rate::Ticker(ticker) => ticker.last,
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("{msg}")]
+pub struct RatesFileError {
+    msg: String,
+}
+
+impl RatesFileConsumer {
+    /// Checks the file up front, as it is read again on every refresh, where errors can only panic.
+    pub fn new(path: impl Into<PathBuf>) -> Result<Self, RatesFileError> {
+        let consumer = Self { path: path.into() };
+        consumer.read_rates()?;
+
+        Ok(consumer)
+    }
+
+    fn read_rates(&self) -> Result<HashMap<Fiat, f64>, RatesFileError> {
+        let content = fs::read_to_string(&self.path).map_err(|e| RatesFileError {
+            msg: format!("Unable to read rates file {}: {e}", self.path.display()),
+        })?;
+        let rates: HashMap<String, Rate> =
+            deserialize_file(&self.path, &content).map_err(|e| RatesFileError {
+                msg: format!("Unable to parse rates file {}: {e}", self.path.display()),
+            })?;
+        log::debug!("Read exchange rates from {}", self.path.display());
+
+        Ok(rates
+            .into_iter()
+            .filter_map(|(symbol, rate)| match Fiat::from_str(&symbol) {
+                Ok(fiat) => Some((fiat, rate.price())),
+                Err(_) => {
+                    log::debug!("Ignoring unsupported currency {symbol} in rates file");
+                    None
+                }
+            })
+            .collect())
+    }
+}
+
+impl ExchangeRateApiConsumer for RatesFileConsumer {
+    fn fetch_api(&self) -> HashMap<Fiat, f64> {
+        self.read_rates().unwrap_or_else(|e| panic!("{e}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture(name: &str) -> String {
+        format!("{}/tests/fixtures/{name}", env!("CARGO_MANIFEST_DIR"))
+    }
+
+    #[test]
+    fn reads_ticker_file() {
+        let rates = RatesFileConsumer::new(fixture("ticker.json"))
+            .unwrap()
+            .fetch_api();
+
+        assert_eq!(rates.len(), 29);
+        assert_eq!(rates[&Fiat::USD], 65_000.0);
+        assert_eq!(rates[&Fiat::JPY], 9_750_000.0);
+    }
+
+    #[test]
+    fn reads_yaml_price_map() {
+        let rates = RatesFileConsumer::new(fixture("rates.yaml"))
+            .unwrap()
+            .fetch_api();
+
+        assert_eq!(rates.len(), 3);
+        assert_eq!(rates[&Fiat::USD], 65_000.0);
+        assert_eq!(rates[&Fiat::CHF], 57_500.5);
+    }
+
+    #[test]
+    fn invalid_file_is_an_error() {
+        let path = std::env::temp_dir().join("bitcoinvert_invalid_rates.json");
+        fs::write(&path, r#"{"USD": "a lot"}"#).unwrap();
+
+        let result = RatesFileConsumer::new(&path);
+
+        assert!(result
+            .err()
+            .unwrap()
+            .to_string()
+            .starts_with("Unable to parse rates file"));
+    }
+
+    #[test]
+    fn missing_file_is_an_error() {
+        let result = RatesFileConsumer::new("does/not/exist.json");
+
+        assert!(result
+            .err()
+            .unwrap()
+            .to_string()
+            .starts_with("Unable to read rates file does/not/exist.json"));
+    }
+}
@@ -0,0 +1,120 @@
+use std::error::Error;
+
+use home_config::HomeConfig;
+use tabled::{settings::Style, Table, Tabled};
+
+use bitcoinvert::cost_basis::{self, Gains};
+use bitcoinvert::{
+    check_quoted, BitcoinUnit, Currency, ExchangeRates, Fiat, PriceHistory,
+    PriceHistoryApiConsumer, PriceHistoryFileConsumer,
+};
+
+use crate::cli_input::{CliInput, GainsArgs};
+use crate::print;
+
+const LEDGER_FILE: &str = "ledger.csv";
+
+#[derive(Tabled)]
+struct TaxYear {
+    #[tabled(rename = "tax year")]
+    year: i32,
+    proceeds: String,
+    #[tabled(rename = "cost basis")]
+    cost: String,
+    #[tabled(rename = "realized gain")]
+    gain: String,
+}
+
+/// Prints the realized gains per tax year and the unrealized gains of the coins still held at the
+/// current rate.
+pub fn run(args: &GainsArgs, rates: &dyn ExchangeRates) -> Result<(), Box<dyn Error>> {
+    let path = match &args.ledger {
+        Some(path) => path.clone(),
+        None => HomeConfig::with_config_dir(env!("CARGO_PKG_NAME"), LEDGER_FILE)
+            .path()
+            .clone(),
+    };
+    if !path.exists() {
+        return Err(format!(
+            "No transactions found. List them in {} with the columns date,type,amount,price.",
+            path.display()
+        )
+        .into());
+    }
+
+    let currency = CliInput::parse_currency(&args.currency)
+        .ok_or_else(|| format!("\"{}\" is not a valid currency!", args.currency))?;
+    check_quoted(&*currency, rates)?;
+    let mut transactions = cost_basis::read_ledger(&path)?;
+    if transactions
+        .iter()
+        .any(|transaction| transaction.price.is_none())
+    {
+        cost_basis::fill_prices(&mut transactions, &*price_history(args, &*currency)?)?;
+    }
+    let gains = cost_basis::compute(&transactions, args.method)?;
+
+    println!("Cost basis method: {}\n", args.method);
+    if !gains.realized.is_empty() {
+        let years: Vec<TaxYear> = gains
+            .realized
+            .iter()
+            .map(|(year, realized)| TaxYear {
+                year: *year,
+                proceeds: format(realized.proceeds, &*currency),
+                cost: format(realized.cost, &*currency),
+                gain: format(realized.gain(), &*currency),
+            })
+            .collect();
+        println!("{}\n", Table::new(years).with(Style::psql()));
+    }
+
+    print_holdings(&gains, &*currency, rates);
+
+    Ok(())
+}
+
+/// The source of the prices missing in the ledger, which must be in the ledger's currency.
+fn price_history(
+    args: &GainsArgs,
+    currency: &dyn Currency,
+) -> Result<Box<dyn PriceHistory>, Box<dyn Error>> {
+    match &args.prices {
+        Some(path) => Ok(Box::new(PriceHistoryFileConsumer::new(path)?)),
+        None if currency.as_fiat() == Some(&Fiat::USD) => {
+            Ok(Box::new(PriceHistoryApiConsumer::default()))
+        }
+        None => Err(format!(
+            "Historical prices are only available in USD. Add the missing prices in {currency} to \
+             the ledger, or list them with --prices FILE."
+        )
+        .into()),
+    }
+}
+
+fn print_holdings(gains: &Gains, currency: &dyn Currency, rates: &dyn ExchangeRates) {
+    let holdings = gains.holdings();
+    let price = 1.0 / currency.btc_value(rates);
+
+    println!(
+        "Holdings:        {} BTC",
+        print::format_amount(
+            print::round(holdings, &BitcoinUnit::BTC, false),
+            &BitcoinUnit::BTC,
+            None
+        )
+    );
+    println!("Cost basis:      {}", format(gains.cost_basis(), currency));
+    println!("Value:           {}", format(holdings * price, currency));
+    println!(
+        "Unrealized gain: {}",
+        format(gains.unrealized(price), currency)
+    );
+}
+
+fn format(value: f64, currency: &dyn Currency) -> String {
+    format!(
+        "{} {currency}",
+        print::format_amount(print::round(value, currency, false), currency, None)
+    )
+}
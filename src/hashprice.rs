@@ -0,0 +1,25 @@
+const BLOCKS_PER_DAY: f64 = 144.0;
+const HASHES_PER_PETAHASH: f64 = 1e15;
+
+/// The satoshis a miner with `hashrate` can expect to earn per day, if the whole network mines
+/// with `network_hashrate` for a reward of `block_reward_sat` per block. Both hashrates in H/s.
+pub fn expected_daily_sats(hashrate: f64, network_hashrate: f64, block_reward_sat: f64) -> f64 {
+    hashrate / network_hashrate * BLOCKS_PER_DAY * block_reward_sat
+}
+
+/// The expected satoshis per PH/s per day, the usual way to compare mining revenue.
+pub fn hashprice(network_hashrate: f64, block_reward_sat: f64) -> f64 {
+    expected_daily_sats(HASHES_PER_PETAHASH, network_hashrate, block_reward_sat)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn earns_share_of_the_daily_rewards() {
+        // 200 TH/s of 600 EH/s at 3.125 BTC per block
+        assert!((expected_daily_sats(200e12, 600e18, 312_500_000.0) - 15_000.0).abs() < 1e-6);
+        assert!((hashprice(600e18, 312_500_000.0) - 75_000.0).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,98 @@
+pub const MAX_SUPPLY_BTC: f64 = 21_000_000.0;
+const INITIAL_SUBSIDY_SAT: u64 = 50 * 100_000_000;
+pub const HALVING_INTERVAL: u64 = 210_000;
+// Shifting by 64 or more bits would overflow, Bitcoin Core returns no subsidy from then on
+const MAX_HALVINGS: u64 = 64;
+const TARGET_BLOCK_SECONDS: u64 = 600;
+// Block 840,000, the fourth halving, mined at 2024-04-20 00:09:27 UTC
+const ANCHOR_HEIGHT: u64 = 840_000;
+const ANCHOR_TIME: u64 = 1_713_571_767;
+
+/// The subsidy of the block at `height` in satoshis, without any fees.
+pub fn block_subsidy(height: u64) -> u64 {
+    match height / HALVING_INTERVAL {
+        halvings if halvings >= MAX_HALVINGS => 0,
+        halvings => INITIAL_SUBSIDY_SAT >> halvings,
+    }
+}
+
+/// The height of the first block after `height` with a halved subsidy.
+pub fn next_halving(height: u64) -> u64 {
+    (height / HALVING_INTERVAL + 1) * HALVING_INTERVAL
+}
+
+/// The satoshis issued by all blocks up to and including `height`, counting the unspendable
+/// genesis block like most supply figures do.
+pub fn issued_supply(height: u64) -> u64 {
+    let completed_eras = height / HALVING_INTERVAL;
+    let full_eras: u64 = (0..completed_eras.min(MAX_HALVINGS))
+        .map(|era| HALVING_INTERVAL * block_subsidy(era * HALVING_INTERVAL))
+        .sum();
+    let current_era = (height % HALVING_INTERVAL + 1) * block_subsidy(height);
+
+    full_eras + current_era
+}
+
+/// Estimates the height of the block mined at `unix_time`, assuming one block every 10 minutes
+/// since the fourth halving.
+pub fn estimate_height(unix_time: u64) -> u64 {
+    let blocks = (unix_time.abs_diff(ANCHOR_TIME) / TARGET_BLOCK_SECONDS) as i64;
+    let offset = if unix_time >= ANCHOR_TIME {
+        blocks
+    } else {
+        -blocks
+    };
+
+    ANCHOR_HEIGHT.saturating_add_signed(offset)
+}
+
+/// Estimates when the block at `height` is (or was) mined, as a UNIX timestamp.
+pub fn estimate_time(height: u64) -> u64 {
+    let seconds = height.abs_diff(ANCHOR_HEIGHT) * TARGET_BLOCK_SECONDS;
+
+    if height >= ANCHOR_HEIGHT {
+        ANCHOR_TIME + seconds
+    } else {
+        ANCHOR_TIME.saturating_sub(seconds)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn subsidy_halves_every_210000_blocks() {
+        assert_eq!(block_subsidy(0), 5_000_000_000);
+        assert_eq!(block_subsidy(209_999), 5_000_000_000);
+        assert_eq!(block_subsidy(210_000), 2_500_000_000);
+        assert_eq!(block_subsidy(840_000), 312_500_000);
+        assert_eq!(block_subsidy(6_930_000), 0);
+        assert_eq!(block_subsidy(u64::MAX), 0);
+    }
+
+    #[test]
+    fn finds_next_halving() {
+        assert_eq!(next_halving(0), 210_000);
+        assert_eq!(next_halving(839_999), 840_000);
+        assert_eq!(next_halving(840_000), 1_050_000);
+    }
+
+    #[test]
+    fn sums_issued_supply() {
+        assert_eq!(issued_supply(0), 5_000_000_000);
+        assert_eq!(issued_supply(209_999), 1_050_000_000_000_000);
+        assert_eq!(issued_supply(839_999), 1_968_750_000_000_000);
+        // Just shy of 21 million BTC, due to the rounding of the subsidies
+        assert_eq!(issued_supply(u64::MAX / 2), 2_099_999_997_690_000);
+    }
+
+    #[test]
+    fn estimates_height_from_time() {
+        assert_eq!(estimate_height(ANCHOR_TIME), ANCHOR_HEIGHT);
+        assert_eq!(estimate_height(ANCHOR_TIME + 6_000), ANCHOR_HEIGHT + 10);
+        assert_eq!(estimate_height(ANCHOR_TIME - 6_000), ANCHOR_HEIGHT - 10);
+        assert_eq!(estimate_height(0), 0);
+        assert_eq!(estimate_time(ANCHOR_HEIGHT + 10), ANCHOR_TIME + 6_000);
+    }
+}
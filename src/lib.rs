@@ -0,0 +1,58 @@
+//! Conversions between Bitcoin denominations and fiat currencies.
+//!
+//! The exchange rates are injected into the [`Converter`], so any [`ExchangeRates`] source can be
+//! used, e.g. an [`ExchangeRateProvider`] fetching them from blockchain.info:
+//!
+//! ```no_run
+//! use std::sync::Mutex;
+//!
+//! use bitcoinvert::currency::btc::BitcoinUnit;
+//! use bitcoinvert::currency::fiat::Fiat;
+//! use bitcoinvert::fiat_rates::blockchain_info_consumer::ApiConsumer;
+//! use bitcoinvert::{Converter, ExchangeRateProvider};
+//!
+//! let converter = Converter::new(Mutex::new(ExchangeRateProvider::new(ApiConsumer)));
+//! let conversion = converter.convert(21_000.0, &BitcoinUnit::SAT, &Fiat::USD);
+//!
+//! println!("{} {}", conversion.value, conversion.to);
+//! ```
+
+pub mod bip21;
+pub mod bolt11;
+pub mod converter;
+pub mod cost_basis;
+pub mod currencies;
+pub mod currency;
+pub mod fee_estimate;
+pub mod fiat_rates;
+pub mod hashprice;
+pub mod issuance;
+#[cfg(test)]
+mod mock_server;
+pub mod routing_fee;
+pub mod sanity;
+pub mod tx_size;
+
+pub use bip21::PaymentUri;
+pub use bolt11::Invoice;
+pub use converter::{Conversion, Converter};
+pub use currencies::Currencies;
+pub use currency::btc::BitcoinUnit;
+pub use currency::commodity::{Commodity, Metal};
+pub use currency::custom::CustomUnit;
+pub use currency::fiat::Fiat;
+pub use currency::Currency;
+pub use fiat_rates::ecb_consumer::EcbConsumer;
+pub use fiat_rates::exchange_rate_provider::{
+    ExchangeRateApiConsumer, ExchangeRateProvider, ExchangeRates,
+};
+pub use fiat_rates::fx_rates::FxRates;
+pub use fiat_rates::metal_prices::{
+    MetalApiConsumer, MetalPriceConsumer, MetalPrices, MetalPricesFileConsumer,
+};
+pub use fiat_rates::price_history::{
+    PriceHistory, PriceHistoryApiConsumer, PriceHistoryFileConsumer,
+};
+pub use fiat_rates::rate_overrides::RateOverrides;
+pub use fiat_rates::rates_file_consumer::RatesFileConsumer;
+pub use fiat_rates::{check_quoted, MissingRateError};
@@ -0,0 +1,56 @@
+use std::error::Error;
+
+use thousands::Separable;
+
+use bitcoinvert::routing_fee::{self, FeePolicy};
+use bitcoinvert::ExchangeRates;
+use bitcoinvert::{check_quoted, BitcoinUnit};
+
+use crate::cli_input::{CliInput, LnFeeArgs};
+use crate::{fee, print};
+
+/// Prints the fee of routing a payment over Lightning in every requested or configured output
+/// currency, along with the effective fee rate.
+pub fn run(args: &LnFeeArgs, rates: &dyn ExchangeRates) -> Result<(), Box<dyn Error>> {
+    let amount = CliInput::parse_amount(Some(args.amount.clone()))?;
+    let currency = CliInput::parse_input_currency(&args.currency)?;
+    check_quoted(&*currency, rates)?;
+    let msat_value = BitcoinUnit::MSAT.btc_value();
+    let amount_msat = (amount * currency.btc_value(rates) / msat_value).round() as u64;
+    if amount_msat == 0 {
+        return Err("The amount to route must be at least 1 MSAT".into());
+    }
+
+    let policy = FeePolicy {
+        base_msat: args.base,
+        ppm: args.ppm,
+    };
+    let fee_msat = policy
+        .route_fee(amount_msat, args.hops)
+        .ok_or("The routing fee is too large to calculate")?;
+    let ppm = routing_fee::effective_ppm(fee_msat, amount_msat);
+
+    println!(
+        "Routing fee for {} MSAT over {} hop(s) at {} msat + {} ppm: {} MSAT",
+        amount_msat.separate_with_commas(),
+        args.hops,
+        policy.base_msat.separate_with_commas(),
+        policy.ppm,
+        fee_msat.separate_with_commas()
+    );
+    println!(
+        "Effective fee rate: {:.1} ppm ({:.4}%)\n",
+        ppm,
+        ppm / 10_000.0
+    );
+
+    print::multi_line(
+        fee_msat as f64 * msat_value,
+        &fee::output_currencies(&args.currencies, rates)?,
+        rates,
+        false,
+        None,
+    );
+
+    Ok(())
+}
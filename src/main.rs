@@ -1,21 +1,128 @@
 pub mod cli_input;
-pub mod currencies;
-pub mod currency;
+mod cross_rate;
 pub mod defaults;
-pub mod fiat_rates;
+mod fee;
+mod gains;
+mod lnfee;
+mod mining;
+mod portfolio;
 mod print;
+mod qr;
+mod request;
+mod serve;
+mod subsidy;
+mod tui;
+mod watch;
 
+use std::collections::HashMap;
+use std::error::Error;
+use std::path::{Path, PathBuf};
 use std::process;
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
 
+use bitcoinvert::fiat_rates::blockchain_info_consumer::{self, ApiConsumer};
+use bitcoinvert::{check_quoted, sanity, MissingRateError};
+use bitcoinvert::{
+    Currency, EcbConsumer, ExchangeRateApiConsumer, ExchangeRateProvider, ExchangeRates, Fiat,
+    MetalApiConsumer, MetalPriceConsumer, MetalPrices, MetalPricesFileConsumer, PaymentUri,
+    RateOverrides, RatesFileConsumer,
+};
+use clap::Parser;
 use colored::*;
 
-use crate::cli_input::CliInput;
-use crate::currency::Currency;
+use crate::cli_input::{Args, CliInput, Command};
+use crate::defaults::Defaults;
+use crate::request::RateSource;
+
+const ECB_RATES_HINT: &str = "Rates for more currencies are available with --ecb-rates.";
+
+type ExchangeRateSource = Mutex<ExchangeRateProvider<Box<dyn ExchangeRateApiConsumer>>>;
+type NamedRates = (String, Box<dyn ExchangeRates>);
 
 fn main() {
     env_logger::init();
 
-    let cli_input = match CliInput::parse() {
+    let mut args = Args::parse();
+
+    // Without --rates-file, the configured one is only looked up once a rate is needed
+    let rates_file = args.rates_file.take();
+
+    let ecb_rates = args
+        .ecb_rates
+        .take()
+        .map(|source| (source, args.ecb_anchor.clone()));
+
+    let pinned_rates: HashMap<Fiat, f64> = args.rates.drain(..).collect();
+
+    let rates = match exchange_rates(rates_file.as_deref(), ecb_rates.clone()) {
+        Ok(rates) => RateOverrides::new(pinned_rates.clone(), rates),
+        Err(e) => {
+            eprintln!("{e}");
+            process::exit(exitcode::NOINPUT);
+        }
+    };
+
+    let rates = match metal_prices(args.metal_prices.as_deref()) {
+        Ok(metal_prices) => MetalPrices::new(metal_prices, rates),
+        Err(e) => {
+            eprintln!("{e}");
+            process::exit(exitcode::NOINPUT);
+        }
+    };
+
+    if let Some(command) = args.command.take() {
+        let result = match command {
+            Command::Tui => {
+                let rates_file = rates_file.or_else(default_rates_file);
+                let description = rate_source(rates_file.as_deref(), ecb_rates.as_ref());
+                alternative_rates(
+                    rates_file,
+                    ecb_rates.is_some(),
+                    &pinned_rates,
+                    args.metal_prices.as_deref(),
+                )
+                .and_then(|alternatives| {
+                    let sources = std::iter::once((description, &rates as &dyn ExchangeRates))
+                        .chain(
+                            alternatives
+                                .iter()
+                                .map(|(name, rates)| (name.clone(), &**rates)),
+                        )
+                        .collect();
+                    tui::run(sources)
+                })
+            }
+            Command::Fee(fee_args) => fee::run(&fee_args, &rates),
+            Command::LnFee(lnfee_args) => lnfee::run(&lnfee_args, &rates),
+            Command::Mining(mining_args) => mining::run(&mining_args, &rates),
+            Command::Portfolio(portfolio_args) => portfolio::run(&portfolio_args, &rates),
+            Command::Gains(gains_args) => gains::run(&gains_args, &rates),
+            Command::Subsidy(subsidy_args) => subsidy::run(&subsidy_args, &rates),
+            Command::Request(request_args) => {
+                let rates_file = rates_file.or_else(default_rates_file);
+                let rate_source = RateSource {
+                    description: rate_source(rates_file.as_deref(), ecb_rates.as_ref()),
+                    pinned: pinned_rates.into_keys().collect(),
+                };
+                request::run(&request_args, &rates, &rate_source)
+            }
+            Command::Serve { listen, cache_ttl } => {
+                serve::run(&listen, Duration::from_secs(cache_ttl), &rates)
+            }
+        };
+
+        if let Err(e) = result {
+            if let Some(e) = e.downcast_ref::<MissingRateError>() {
+                report_missing_rate(e);
+            }
+            eprintln!("{e}");
+            process::exit(exitcode::SOFTWARE);
+        }
+        return;
+    }
+
+    let cli_input = match CliInput::try_from(args) {
         Ok(input) => input,
         Err(e) => {
             eprintln!("{e}");
@@ -23,36 +130,239 @@ fn main() {
         }
     };
 
-    let value_in_btc = cli_input.amount * cli_input.input_currency.btc_value();
+    if cli_input.output_currencies.len() > 1 && cli_input.clean {
+        eprintln!(
+            "\n{}\n",
+            "Cannot use clean mode for multi currency output"
+                .to_string()
+                .yellow()
+        );
+    }
 
-    if cli_input.output_currencies.len() == 1 {
-        let mut output_value = value_in_btc / cli_input.output_currencies[0].btc_value();
+    let is_payment_request = cli_input.payment_uri.is_some() || cli_input.invoice.is_some();
+    if is_payment_request && !cli_input.clean && cli_input.bip21.is_none() {
+        print::payment_request(cli_input.payment_uri.as_ref(), cli_input.invoice.as_ref());
+    }
 
-        if cli_input.integer {
-            output_value = output_value.round();
+    exit_if_unquoted(&*cli_input.input_currency, &rates);
+    let value_in_btc = cli_input.amount * cli_input.input_currency.btc_value(&rates);
+
+    let warnings = sanity::check_amount(cli_input.amount, &*cli_input.input_currency, value_in_btc);
+    for warning in &warnings {
+        if cli_input.strict {
+            eprintln!("{warning}");
         } else {
-            output_value = cli_input.output_currencies[0].round_value(output_value);
+            eprintln!("{}", format!("Warning: {warning}").yellow());
+        }
+    }
+    if cli_input.strict && !warnings.is_empty() {
+        process::exit(exitcode::DATAERR);
+    }
+
+    // A payment URI is only in BTC, so the output currencies don't need a rate then
+    if cli_input.bip21.is_none() {
+        for currency in &cli_input.output_currencies {
+            exit_if_unquoted(&**currency, &rates);
         }
+    }
+
+    if let Some(interval) = cli_input.watch {
+        watch::run(&cli_input, interval, &rates);
+    }
+
+    if let Some(address) = &cli_input.bip21 {
+        match PaymentUri::new(address, value_in_btc) {
+            Ok(payment_uri) => {
+                println!("{payment_uri}");
+                let qr = qr::output(
+                    &payment_uri.to_string(),
+                    cli_input.qr,
+                    cli_input.qr_file.as_deref(),
+                );
+                if let Err(e) = qr {
+                    eprintln!("{e}");
+                    process::exit(exitcode::CANTCREAT);
+                }
+            }
+            Err(e) => {
+                eprintln!("{e}");
+                process::exit(exitcode::USAGE);
+            }
+        }
+        return;
+    }
+
+    if cli_input.output_currencies.len() == 1 {
+        let output_currency = &*cli_input.output_currencies[0];
+
+        let output_value = match (
+            cli_input.input_currency.as_fiat(),
+            output_currency.as_fiat(),
+        ) {
+            (Some(from), Some(to)) if from != to => {
+                let output_value = cross_rate::convert(
+                    cli_input.amount,
+                    from,
+                    to,
+                    &rates,
+                    cli_input.fx_rates.as_ref(),
+                    !cli_input.clean,
+                );
+                print::round(output_value, output_currency, cli_input.integer)
+            }
+            _ => print::output_value(value_in_btc, output_currency, &rates, cli_input.integer),
+        };
 
         print::single_line(
             output_value,
-            &*cli_input.output_currencies[0],
+            output_currency,
             cli_input.clean,
+            cli_input.sat_grouping,
         );
     } else {
-        if cli_input.clean {
-            eprintln!(
-                "\n{}\n",
-                "Cannot use clean mode for multi currency output"
-                    .to_string()
-                    .yellow()
-            );
-        }
-
         print::multi_line(
             value_in_btc,
             &cli_input.output_currencies,
+            &rates,
             cli_input.integer,
+            cli_input.sat_grouping,
         );
     }
 }
+
+fn exit_if_unquoted(currency: &dyn Currency, rates: &dyn ExchangeRates) {
+    if let Err(e) = check_quoted(currency, rates) {
+        report_missing_rate(&e);
+    }
+}
+
+fn report_missing_rate(e: &MissingRateError) -> ! {
+    if e.is_fiat() {
+        eprintln!("{e}. {ECB_RATES_HINT}");
+    } else {
+        eprintln!("{e}");
+    }
+    process::exit(exitcode::UNAVAILABLE);
+}
+
+fn exchange_rates(
+    rates_file: Option<&Path>,
+    ecb_rates: Option<(String, Fiat)>,
+) -> Result<ExchangeRateSource, Box<dyn Error>> {
+    let mut data_source: Box<dyn ExchangeRateApiConsumer> = match rates_file {
+        Some(path) => Box::new(RatesFileConsumer::new(path)?),
+        None => Box::new(DefaultRatesConsumer::default()),
+    };
+
+    if let Some((source, anchor)) = ecb_rates {
+        data_source = Box::new(EcbConsumer::new(&source, anchor, data_source)?);
+    }
+
+    Ok(Mutex::new(ExchangeRateProvider::new(data_source)))
+}
+
+/// The exchange rate sources the dashboard can switch to besides the one in use: the plain rates
+/// file if the ECB rates are applied to it, and the API if a rates file or the ECB rates are used.
+/// They all have the same pinned rates and metal prices.
+fn alternative_rates(
+    rates_file: Option<PathBuf>,
+    ecb_rates: bool,
+    pinned_rates: &HashMap<Fiat, f64>,
+    metal_prices_file: Option<&Path>,
+) -> Result<Vec<NamedRates>, Box<dyn Error>> {
+    let mut data_sources: Vec<(String, Box<dyn ExchangeRateApiConsumer>)> = Vec::new();
+    if let Some(path) = rates_file.as_ref().filter(|_| ecb_rates) {
+        data_sources.push((
+            path.display().to_string(),
+            Box::new(RatesFileConsumer::new(path)?),
+        ));
+    }
+    if rates_file.is_some() || ecb_rates {
+        data_sources.push((
+            blockchain_info_consumer::SOURCE_API.to_string(),
+            Box::new(ApiConsumer),
+        ));
+    }
+
+    data_sources
+        .into_iter()
+        .map(|(name, data_source)| {
+            let rates = RateOverrides::new(
+                pinned_rates.clone(),
+                Mutex::new(ExchangeRateProvider::new(data_source)),
+            );
+            let rates: Box<dyn ExchangeRates> =
+                Box::new(MetalPrices::new(metal_prices(metal_prices_file)?, rates));
+            Ok((name, rates))
+        })
+        .collect()
+}
+
+/// The rates file configured in defaults.yaml, if any. A broken configuration isn't fatal, as the
+/// rates can still be fetched from the API.
+fn default_rates_file() -> Option<PathBuf> {
+    Defaults::get_default_rates_file().unwrap_or_else(|e| {
+        warn_rates_fallback(&format!("Failed to load default rates file: {e}"));
+        None
+    })
+}
+
+fn warn_rates_fallback(message: &str) {
+    eprintln!(
+        "{}",
+        format!(
+            "{message}\nFetching the rates from {} instead.",
+            blockchain_info_consumer::SOURCE_API
+        )
+        .yellow()
+    );
+}
+
+/// Reads the rates from the rates file configured in defaults.yaml, or else fetches them from the
+/// API. The defaults are only read once a rate is needed, so conversions between bitcoin units
+/// work without them.
+#[derive(Default)]
+struct DefaultRatesConsumer {
+    rates_file: OnceLock<Option<RatesFileConsumer>>,
+}
+
+impl ExchangeRateApiConsumer for DefaultRatesConsumer {
+    fn fetch_api(&self) -> HashMap<Fiat, f64> {
+        let rates_file = self.rates_file.get_or_init(|| {
+            let path = default_rates_file()?;
+            RatesFileConsumer::new(path)
+                .map_err(|e| warn_rates_fallback(&e.to_string()))
+                .ok()
+        });
+
+        match rates_file {
+            Some(rates_file) => rates_file.fetch_api(),
+            None => ApiConsumer.fetch_api(),
+        }
+    }
+}
+
+fn rate_source(rates_file: Option<&Path>, ecb_rates: Option<&(String, Fiat)>) -> String {
+    let source = match rates_file {
+        Some(path) => path.display().to_string(),
+        None => blockchain_info_consumer::SOURCE_API.to_string(),
+    };
+
+    match ecb_rates {
+        Some((ecb_source, anchor)) => {
+            format!(
+                "{ecb_source} (ECB reference rates applied to the {anchor} price from {source})"
+            )
+        }
+        None => source,
+    }
+}
+
+fn metal_prices(
+    metal_prices_file: Option<&Path>,
+) -> Result<Box<dyn MetalPriceConsumer>, Box<dyn Error>> {
+    Ok(match metal_prices_file {
+        Some(path) => Box::new(MetalPricesFileConsumer::new(path)?),
+        None => Box::new(MetalApiConsumer),
+    })
+}
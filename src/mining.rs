@@ -0,0 +1,72 @@
+use std::error::Error;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use thousands::Separable;
+
+use bitcoinvert::{check_quoted, BitcoinUnit, Currency, ExchangeRates};
+use bitcoinvert::{hashprice, issuance};
+
+use crate::cli_input::{CliInput, MiningArgs};
+use crate::print;
+
+const HOURS_PER_DAY: f64 = 24.0;
+const WATTS_PER_KILOWATT: f64 = 1_000.0;
+
+/// Prints the expected daily revenue of a miner, the cost of its electricity and the profit,
+/// all in the currency the electricity is paid in.
+pub fn run(args: &MiningArgs, rates: &dyn ExchangeRates) -> Result<(), Box<dyn Error>> {
+    let (price_per_kwh, currency) = &args.electricity;
+    let currency = CliInput::parse_currency(currency)
+        .ok_or_else(|| format!("\"{currency}\" is not a valid currency!"))?;
+    check_quoted(&*currency, rates)?;
+
+    let height = match args.height {
+        Some(height) => height,
+        None => issuance::estimate_height(SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs()),
+    };
+    let block_reward_sat = issuance::block_subsidy(height) as f64 + args.fees as f64 / 1_000.0;
+
+    let daily_sats =
+        hashprice::expected_daily_sats(args.hashrate, args.network_hashrate, block_reward_sat);
+    let hashprice = hashprice::hashprice(args.network_hashrate, block_reward_sat);
+    let revenue = sats_in(daily_sats, &*currency, rates);
+    let cost = args.power / WATTS_PER_KILOWATT * HOURS_PER_DAY * price_per_kwh;
+
+    println!(
+        "Block reward at height {}: {} SAT",
+        height.separate_with_commas(),
+        block_reward_sat.round().separate_with_commas()
+    );
+    println!(
+        "Share of the network hashrate: {:.6}%\n",
+        args.hashrate / args.network_hashrate * 100.0
+    );
+    println!(
+        "Revenue:     {} SAT = {} per day",
+        daily_sats.round().separate_with_commas(),
+        amount(revenue, &*currency)
+    );
+    println!("Electricity: {} per day", amount(cost, &*currency));
+    println!(
+        "Profit:      {} per day",
+        amount(revenue - cost, &*currency)
+    );
+    println!(
+        "Hashprice:   {} SAT = {} per PH/s per day",
+        hashprice.round().separate_with_commas(),
+        amount(sats_in(hashprice, &*currency, rates), &*currency)
+    );
+
+    Ok(())
+}
+
+fn sats_in(sats: f64, currency: &dyn Currency, rates: &dyn ExchangeRates) -> f64 {
+    print::output_value(sats * BitcoinUnit::SAT.btc_value(), currency, rates, false)
+}
+
+fn amount(value: f64, currency: &dyn Currency) -> String {
+    format!(
+        "{} {currency}",
+        print::format_amount(print::round(value, currency, false), currency, None)
+    )
+}
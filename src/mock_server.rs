@@ -0,0 +1,21 @@
+use std::thread;
+
+use tiny_http::{Request, Response, Server};
+
+/// Answers a single request with `body` after `check`ing it, returning the server's URL.
+pub(crate) fn serve_once(
+    body: &'static str,
+    check: impl FnOnce(&mut Request) + Send + 'static,
+) -> String {
+    let server = Server::http("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", server.server_addr());
+
+    thread::spawn(move || {
+        let mut request = server.recv().unwrap();
+        check(&mut request);
+
+        request.respond(Response::from_string(body)).unwrap();
+    });
+
+    url
+}
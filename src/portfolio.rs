@@ -0,0 +1,165 @@
+use std::error::Error;
+use std::path::Path;
+
+use home_config::HomeConfig;
+use serde_yml::Mapping;
+use tabled::builder::Builder;
+use tabled::settings::Style;
+
+use bitcoinvert::{check_quoted, Currency, ExchangeRates};
+
+use crate::cli_input::{CliInput, PortfolioArgs};
+use crate::{fee, print};
+
+const HOLDINGS_FILE: &str = "holdings.yaml";
+
+struct Holding {
+    name: String,
+    amount: f64,
+    currency: Box<dyn Currency>,
+}
+
+/// Prints every holding and the total in all requested or configured output currencies, along
+/// with the share of every holding in the total.
+pub fn run(args: &PortfolioArgs, rates: &dyn ExchangeRates) -> Result<(), Box<dyn Error>> {
+    let path = match &args.holdings {
+        Some(path) => path.clone(),
+        None => HomeConfig::with_config_dir(env!("CARGO_PKG_NAME"), HOLDINGS_FILE)
+            .path()
+            .clone(),
+    };
+    let holdings = read_holdings(&path)?;
+    for holding in &holdings {
+        check_quoted(&*holding.currency, rates)?;
+    }
+    let currencies = fee::output_currencies(&args.currencies, rates)?;
+
+    let values_in_btc: Vec<f64> = holdings
+        .iter()
+        .map(|holding| holding.amount * holding.currency.btc_value(rates))
+        .collect();
+    let total_in_btc: f64 = values_in_btc.iter().sum();
+
+    let mut table = Builder::default();
+    table.push_record(
+        ["holding".to_string(), "amount".to_string()]
+            .into_iter()
+            .chain(currencies.iter().map(|currency| currency.to_string()))
+            .chain(["allocation".to_string()]),
+    );
+
+    for (holding, value_in_btc) in holdings.iter().zip(&values_in_btc) {
+        let amount = print::format_amount(
+            print::round(holding.amount, &*holding.currency, false),
+            &*holding.currency,
+            None,
+        );
+        table.push_record(
+            [
+                holding.name.clone(),
+                format!("{amount} {}", holding.currency),
+            ]
+            .into_iter()
+            .chain(values(*value_in_btc, &currencies, rates))
+            .chain([allocation(*value_in_btc, total_in_btc)]),
+        );
+    }
+    table.push_record(
+        ["total".to_string(), String::new()]
+            .into_iter()
+            .chain(values(total_in_btc, &currencies, rates))
+            .chain([allocation(total_in_btc, total_in_btc)]),
+    );
+
+    println!("{}", table.build().with(Style::psql()));
+
+    Ok(())
+}
+
+fn values<'a>(
+    value_in_btc: f64,
+    currencies: &'a [Box<dyn Currency>],
+    rates: &'a dyn ExchangeRates,
+) -> impl Iterator<Item = String> + 'a {
+    currencies.iter().map(move |currency| {
+        let value = print::output_value(value_in_btc, &**currency, rates, false);
+        print::format_amount(value, &**currency, None)
+    })
+}
+
+fn allocation(value_in_btc: f64, total_in_btc: f64) -> String {
+    if total_in_btc == 0.0 {
+        return "-".to_string();
+    }
+    format!("{:.1}%", value_in_btc / total_in_btc * 100.0)
+}
+
+/// Reads holdings of the form `cold: 0.8 BTC`, keeping their order.
+fn read_holdings(path: &Path) -> Result<Vec<Holding>, Box<dyn Error>> {
+    if !path.exists() {
+        return Err(format!(
+            "No holdings found. List them in {} like \"cold: 0.8 BTC\", one per line.",
+            path.display()
+        )
+        .into());
+    }
+
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| format!("Unable to read holdings file {}: {e}", path.display()))?;
+    parse_holdings(&content)
+        .map_err(|e| format!("Unable to parse holdings file {}: {e}", path.display()).into())
+}
+
+fn parse_holdings(yaml: &str) -> Result<Vec<Holding>, Box<dyn Error>> {
+    let holdings: Mapping = serde_yml::from_str(yaml)?;
+
+    holdings
+        .iter()
+        .map(|(name, balance)| {
+            let name = name.as_str();
+            let balance = balance
+                .as_str()
+                .ok_or(format!("The balance of {name} must be like \"0.8 BTC\""))?;
+            parse_holding(name, balance)
+        })
+        .collect()
+}
+
+fn parse_holding(name: &str, balance: &str) -> Result<Holding, Box<dyn Error>> {
+    let (amount, currency) = balance
+        .trim()
+        .split_once(char::is_whitespace)
+        .ok_or(format!("The balance of {name} must be like \"0.8 BTC\""))?;
+
+    Ok(Holding {
+        name: name.to_string(),
+        amount: CliInput::parse_amount(Some(amount.to_string()))?,
+        currency: CliInput::parse_currency(currency.trim())
+            .ok_or(format!("\"{currency}\" of {name} is not a valid currency"))?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_holdings_in_order() {
+        let holdings = parse_holdings("cold: 0.8 BTC\nln: 2.1M SAT\ncash: 5000 CHF\n").unwrap();
+
+        assert_eq!(
+            holdings
+                .iter()
+                .map(|holding| format!("{} {} {}", holding.name, holding.amount, holding.currency))
+                .collect::<Vec<_>>(),
+            vec!["cold 0.8 BTC", "ln 2100000 SAT", "cash 5000 CHF"]
+        );
+    }
+
+    #[test]
+    fn invalid_holdings_are_errors() {
+        assert!(parse_holdings("cold: 0.8").is_err());
+        assert!(parse_holdings("cold: 0.8 XYZ").is_err());
+        assert!(parse_holdings("cold: 0.8 BTC\nhot: [1, BTC]").is_err());
+    }
+}
@@ -1,8 +1,27 @@
+use clap::ValueEnum;
+use colored::*;
+use std::time::{SystemTime, UNIX_EPOCH};
 use tabled::settings::Style;
 use tabled::{Table, Tabled};
 use thousands::Separable;
 
-use crate::currency::Currency;
+use bitcoinvert::{BitcoinUnit, Currency, ExchangeRates, Invoice, PaymentUri};
+
+/// Separator between the groups of decimals of BTC amounts, e.g. `0.01 234 567`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum SatGrouping {
+    Space,
+    Comma,
+}
+
+impl SatGrouping {
+    fn separator(self) -> char {
+        match self {
+            SatGrouping::Space => ' ',
+            SatGrouping::Comma => ',',
+        }
+    }
+}
 
 #[derive(Tabled)]
 struct TableRow {
@@ -10,32 +29,218 @@ struct TableRow {
     amount: String,
 }
 
-pub fn multi_line(value_in_btc: f64, currencies: &[Box<dyn Currency>], integer: bool) {
-    let mut data = Vec::new();
+pub fn output_value(
+    value_in_btc: f64,
+    currency: &dyn Currency,
+    rates: &dyn ExchangeRates,
+    integer: bool,
+) -> f64 {
+    round(value_in_btc / currency.btc_value(rates), currency, integer)
+}
+
+pub fn round(value: f64, currency: &dyn Currency, integer: bool) -> f64 {
+    if integer {
+        value.round()
+    } else {
+        currency.round_value(value)
+    }
+}
 
-    for currency in currencies {
-        let mut output_value = value_in_btc / currency.btc_value();
-        if integer {
-            output_value = output_value.round();
-        } else {
-            output_value = currency.round_value(output_value);
+/// Formats BTC amounts with their decimals grouped like `0.01 234 567` if `sat_grouping` is set,
+/// so the satoshis are readable. Any other amount only gets thousand separators.
+pub fn format_amount(
+    value: f64,
+    currency: &dyn Currency,
+    sat_grouping: Option<SatGrouping>,
+) -> String {
+    match sat_grouping {
+        Some(grouping) if currency.as_bitcoin_unit() == Some(&BitcoinUnit::BTC) => {
+            group_sats(value, grouping.separator())
         }
+        _ => value.separate_with_commas(),
+    }
+}
+
+fn group_sats(value: f64, separator: char) -> String {
+    let formatted = format!("{:.8}", value.abs());
+    let (integer, decimals) = formatted.split_once('.').unwrap();
+    let sign = if value < 0.0 { "-" } else { "" };
+
+    format!(
+        "{sign}{}.{}{separator}{}{separator}{}",
+        integer.separate_with_commas(),
+        &decimals[..2],
+        &decimals[2..5],
+        &decimals[5..]
+    )
+}
+
+pub fn multi_line(
+    value_in_btc: f64,
+    currencies: &[Box<dyn Currency>],
+    rates: &dyn ExchangeRates,
+    integer: bool,
+    sat_grouping: Option<SatGrouping>,
+) {
+    multi_line_with_trend(value_in_btc, currencies, rates, integer, sat_grouping, None);
+}
+
+/// Like `multi_line`, but colors every amount that went up (green) or down (red) compared to
+/// `previous`. Returns the printed amounts, so they can be passed as `previous` next time.
+pub fn multi_line_with_trend(
+    value_in_btc: f64,
+    currencies: &[Box<dyn Currency>],
+    rates: &dyn ExchangeRates,
+    integer: bool,
+    sat_grouping: Option<SatGrouping>,
+    previous: Option<&[f64]>,
+) -> Vec<f64> {
+    let mut data = Vec::new();
+    let mut output_values = Vec::new();
+
+    for (i, currency) in currencies.iter().enumerate() {
+        let output_value = output_value(value_in_btc, &**currency, rates, integer);
+        let previous_value = previous.and_then(|previous| previous.get(i).copied());
 
         data.push(TableRow {
             unit: currency.to_string(),
-            amount: output_value.separate_with_commas().to_string(),
+            amount: with_trend(
+                format_amount(output_value, &**currency, sat_grouping),
+                output_value,
+                previous_value,
+            ),
         });
+        output_values.push(output_value);
     }
 
     let table = Table::new(data).with(Style::psql()).to_string();
 
     println!("{}", table);
+
+    output_values
+}
+
+pub fn single_line(
+    output_value: f64,
+    currency: &dyn Currency,
+    clean: bool,
+    sat_grouping: Option<SatGrouping>,
+) {
+    single_line_with_trend(output_value, currency, clean, sat_grouping, None);
 }
 
-pub fn single_line(output_value: f64, currency: &dyn Currency, clean: bool) {
+/// Like `single_line`, but colors the amount green or red if it went up or down compared to
+/// `previous`. Clean output is never colored.
+pub fn single_line_with_trend(
+    output_value: f64,
+    currency: &dyn Currency,
+    clean: bool,
+    sat_grouping: Option<SatGrouping>,
+    previous: Option<f64>,
+) {
     if clean {
         println!("{}", output_value);
     } else {
-        println!("{} {}", output_value.separate_with_commas(), currency);
+        println!(
+            "{} {}",
+            with_trend(
+                format_amount(output_value, currency, sat_grouping),
+                output_value,
+                previous
+            ),
+            currency
+        );
+    }
+}
+
+/// Prints the details of a BIP21 URI and/or Lightning invoice ahead of the converted amount.
+pub fn payment_request(payment_uri: Option<&PaymentUri>, invoice: Option<&Invoice>) {
+    if let Some(payment_uri) = payment_uri {
+        if !payment_uri.address.is_empty() {
+            println!("Address: {}", payment_uri.address);
+        }
+        if let Some(label) = &payment_uri.label {
+            println!("Label: {label}");
+        }
+        if let Some(message) = &payment_uri.message {
+            println!("Message: {message}");
+        }
+    }
+    if let Some(invoice) = invoice {
+        self::invoice(invoice);
+    }
+    println!();
+}
+
+fn invoice(invoice: &Invoice) {
+    if let Some(description) = &invoice.description {
+        println!("Description: {description}");
+    }
+    if let Some(payee) = &invoice.payee {
+        println!("Payee: {payee}");
+    }
+
+    let expires_at = invoice.timestamp.saturating_add(invoice.expiry);
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|now| now.as_secs())
+        .unwrap_or_default();
+    if now >= expires_at {
+        println!("Expiry: {} seconds, {}", invoice.expiry, "expired".red());
+    } else {
+        println!(
+            "Expiry: {} seconds, {} seconds left",
+            invoice.expiry,
+            expires_at - now
+        );
+    }
+}
+
+fn with_trend(text: String, value: f64, previous: Option<f64>) -> String {
+    match previous {
+        Some(previous) if value > previous => text.green().to_string(),
+        Some(previous) if value < previous => text.red().to_string(),
+        _ => text,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bitcoinvert::Fiat;
+
+    #[test]
+    fn groups_sats_of_btc_amounts() {
+        let btc = &BitcoinUnit::BTC;
+
+        assert_eq!(
+            format_amount(0.01234567, btc, Some(SatGrouping::Space)),
+            "0.01 234 567"
+        );
+        assert_eq!(
+            format_amount(0.01234567, btc, Some(SatGrouping::Comma)),
+            "0.01,234,567"
+        );
+        assert_eq!(
+            format_amount(1234.5, btc, Some(SatGrouping::Space)),
+            "1,234.50 000 000"
+        );
+        assert_eq!(
+            format_amount(-0.00000001, btc, Some(SatGrouping::Space)),
+            "-0.00 000 001"
+        );
+        assert_eq!(format_amount(0.01234567, btc, None), "0.01234567");
+    }
+
+    #[test]
+    fn only_groups_btc() {
+        assert_eq!(
+            format_amount(1234.5, &Fiat::USD, Some(SatGrouping::Space)),
+            "1,234.5"
+        );
+        assert_eq!(
+            format_amount(1234.5, &BitcoinUnit::SAT, Some(SatGrouping::Space)),
+            "1,234.5"
+        );
     }
 }
@@ -0,0 +1,149 @@
+use std::error::Error;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use clap::ValueEnum;
+use serde_json::json;
+
+use bitcoinvert::issuance::MAX_SUPPLY_BTC;
+use bitcoinvert::{check_quoted, BitcoinUnit, Currency, ExchangeRates, Fiat, PaymentUri};
+
+use crate::cli_input::{CliInput, RequestArgs};
+use crate::qr;
+
+/// How the requested amount is rounded to a multiple of `--round-to` satoshis.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Rounding {
+    Up,
+    Down,
+    Nearest,
+}
+
+/// Where the exchange rates come from, to record along with a quote.
+pub struct RateSource {
+    pub description: String,
+    pub pinned: Vec<Fiat>,
+}
+
+impl RateSource {
+    fn describe(&self, currency: &dyn Currency) -> String {
+        match currency.as_fiat() {
+            Some(fiat) if self.pinned.contains(fiat) => "fixed rate".to_string(),
+            _ => self.description.clone(),
+        }
+    }
+}
+
+/// Prints a BIP21 payment request for a price in any currency as JSON, together with the rate it
+/// was quoted at and until when the quote is valid.
+pub fn run(
+    args: &RequestArgs,
+    rates: &dyn ExchangeRates,
+    rate_source: &RateSource,
+) -> Result<(), Box<dyn Error>> {
+    let amount = CliInput::parse_amount(Some(args.amount.clone()))?;
+    let currency = CliInput::parse_currency(&args.currency)
+        .ok_or_else(|| format!("\"{}\" is not a valid currency!", args.currency))?;
+    check_quoted(&*currency, rates)?;
+
+    let btc_per_unit = currency.btc_value(rates);
+    let value_in_btc = amount * btc_per_unit;
+    if !value_in_btc.is_finite() || value_in_btc > MAX_SUPPLY_BTC {
+        return Err(format!(
+            "{amount} {currency} is more than the 21 million bitcoin that will ever exist"
+        )
+        .into());
+    }
+    let sats = round_sats(
+        value_in_btc / BitcoinUnit::SAT.btc_value(),
+        args.round_to,
+        args.rounding,
+    )
+    .ok_or_else(|| {
+        format!(
+            "{amount} {currency} is too large to round to {} SAT",
+            args.round_to
+        )
+    })?;
+    if sats == 0 {
+        return Err(format!("{amount} {currency} rounds to 0 SAT").into());
+    }
+    if sats as f64 * BitcoinUnit::SAT.btc_value() > MAX_SUPPLY_BTC {
+        return Err(format!(
+            "{amount} {currency} rounds to more than the 21 million bitcoin that will ever exist"
+        )
+        .into());
+    }
+
+    let mut payment_uri =
+        PaymentUri::new(&args.address, sats as f64 * BitcoinUnit::SAT.btc_value())?;
+    payment_uri.label = args.label.clone();
+    payment_uri.message = args.message.clone();
+
+    let quoted_at = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+    let expires_at = args
+        .valid_for
+        .checked_mul(60)
+        .and_then(|valid_for| quoted_at.checked_add(valid_for))
+        .ok_or_else(|| format!("A quote can't be valid for {} minutes", args.valid_for))?;
+    let quote = json!({
+        "amount": amount,
+        "currency": currency.to_string(),
+        "sats": sats,
+        "rate": currency.round_value(1.0 / btc_per_unit),
+        "source": rate_source.describe(&*currency),
+        "quoted_at": quoted_at,
+        "expires_at": expires_at,
+        "uri": payment_uri.to_string(),
+    });
+
+    if let Some(path) = &args.record {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|e| format!("Unable to open {}: {e}", path.display()))?;
+        writeln!(file, "{quote}")?;
+    }
+
+    println!("{}", serde_json::to_string_pretty(&quote)?);
+
+    qr::output(&payment_uri.to_string(), args.qr, args.qr_file.as_deref())
+}
+
+/// Rounds an amount of satoshis to a multiple of `step`, or `None` if that doesn't fit into a u64.
+fn round_sats(sats: f64, step: u64, rounding: Rounding) -> Option<u64> {
+    // Ignore floating point noise, which would round up whole amounts like 99,800.000000001
+    let steps = (sats * 1e6).round() / 1e6 / step as f64;
+    let steps = match rounding {
+        Rounding::Up => steps.ceil(),
+        Rounding::Down => steps.floor(),
+        Rounding::Nearest => steps.round(),
+    };
+
+    (steps as u64).checked_mul(step)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rounds_to_multiples() {
+        assert_eq!(round_sats(99_800.2, 1, Rounding::Up), Some(99_801));
+        assert_eq!(round_sats(99_800.2, 1, Rounding::Nearest), Some(99_800));
+        assert_eq!(round_sats(99_800.2, 1_000, Rounding::Up), Some(100_000));
+        assert_eq!(round_sats(99_800.2, 1_000, Rounding::Down), Some(99_000));
+        assert_eq!(
+            round_sats(99_500.0, 1_000, Rounding::Nearest),
+            Some(100_000)
+        );
+    }
+
+    #[test]
+    fn rounding_beyond_u64_is_none() {
+        assert_eq!(round_sats(1.0, u64::MAX, Rounding::Up), Some(u64::MAX));
+        assert_eq!(round_sats(1e20, 1_000, Rounding::Up), None);
+    }
+}
@@ -0,0 +1,77 @@
+const PPM: u64 = 1_000_000;
+
+/// Fee policy of a Lightning channel: a base fee plus a proportional fee in parts per million.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FeePolicy {
+    pub base_msat: u64,
+    pub ppm: u64,
+}
+
+impl FeePolicy {
+    /// The fee for forwarding `amount_msat`, rounded down to whole millisatoshis like the nodes do,
+    /// or `None` if it doesn't fit into 64 bits.
+    pub fn fee(&self, amount_msat: u64) -> Option<u64> {
+        let proportional = u128::from(amount_msat) * u128::from(self.ppm) / u128::from(PPM);
+        self.base_msat
+            .checked_add(u64::try_from(proportional).ok()?)
+    }
+
+    /// The total fee to route `amount_msat` over `hops` nodes charging this policy. Every node
+    /// charges on the amount it forwards, which includes the fees of the nodes after it.
+    pub fn route_fee(&self, amount_msat: u64, hops: u32) -> Option<u64> {
+        let forwarded = (0..hops).try_fold(amount_msat, |forwarded, _| {
+            forwarded.checked_add(self.fee(forwarded)?)
+        })?;
+        Some(forwarded - amount_msat)
+    }
+}
+
+/// The fee relative to the amount, in parts per million.
+pub fn effective_ppm(fee_msat: u64, amount_msat: u64) -> f64 {
+    fee_msat as f64 / amount_msat as f64 * PPM as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const POLICY: FeePolicy = FeePolicy {
+        base_msat: 1_000,
+        ppm: 350,
+    };
+
+    #[test]
+    fn charges_base_and_proportional_fee() {
+        assert_eq!(POLICY.fee(250_000_000), Some(88_500));
+        // 0.35 msat are rounded down
+        assert_eq!(POLICY.fee(1_000), Some(1_000));
+    }
+
+    #[test]
+    fn fees_compound_over_hops() {
+        assert_eq!(POLICY.route_fee(250_000_000, 0), Some(0));
+        assert_eq!(POLICY.route_fee(250_000_000, 1), Some(88_500));
+        assert_eq!(POLICY.route_fee(250_000_000, 3), Some(265_591));
+    }
+
+    #[test]
+    fn fees_too_large_to_calculate_are_none() {
+        let greedy = FeePolicy {
+            base_msat: 1_000,
+            ppm: PPM,
+        };
+        assert_eq!(greedy.route_fee(250_000_000, 64), None);
+
+        let expensive = FeePolicy {
+            base_msat: u64::MAX,
+            ppm: 0,
+        };
+        assert_eq!(expensive.fee(1_000_000), Some(u64::MAX));
+        assert_eq!(expensive.route_fee(1_000_000, 1), None);
+    }
+
+    #[test]
+    fn computes_effective_ppm() {
+        assert!((effective_ppm(88_500, 250_000_000) - 354.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,90 @@
+use crate::currency::btc::BitcoinUnit;
+use crate::currency::Currency;
+use crate::issuance::MAX_SUPPLY_BTC;
+
+/// Describes everything implausible about `amount` of `currency`, worth `value_in_btc`: amounts
+/// that aren't finite, negative amounts, more bitcoin than will ever exist, or bitcoin amounts
+/// more precise than their unit allows.
+pub fn check_amount(amount: f64, currency: &dyn Currency, value_in_btc: f64) -> Vec<String> {
+    let mut warnings = Vec::new();
+
+    if !amount.is_finite() {
+        warnings.push(format!("{amount} {currency} is not a finite amount"));
+        return warnings;
+    }
+    if !value_in_btc.is_finite() {
+        warnings.push(format!(
+            "{amount} {currency} converts to {value_in_btc} BTC, the exchange rate may be zero"
+        ));
+    }
+    if amount < 0.0 {
+        warnings.push(format!("{amount} {currency} is negative"));
+    }
+    if value_in_btc.is_finite() && value_in_btc.abs() > MAX_SUPPLY_BTC {
+        let in_btc = match currency.as_bitcoin_unit() {
+            Some(BitcoinUnit::BTC) => String::new(),
+            _ => format!(" {value_in_btc} BTC,"),
+        };
+        warnings.push(format!(
+            "{amount} {currency} is{in_btc} more than the 21 million that will ever exist"
+        ));
+    }
+    if currency.as_bitcoin_unit().is_some()
+        && !has_decimal_places(amount, currency.decimal_places())
+    {
+        warnings.push(format!(
+            "{amount} {currency} is more precise than {currency} supports ({} decimal places)",
+            currency.decimal_places()
+        ));
+    }
+
+    warnings
+}
+
+fn has_decimal_places(amount: f64, decimal_places: u8) -> bool {
+    let scaled = amount * 10_f64.powi(decimal_places.into());
+    // Tolerate the inaccuracy of decimal fractions like 0.1 in binary
+    (scaled - scaled.round()).abs() <= 1e-6
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::currency::fiat::Fiat;
+
+    #[test]
+    fn plausible_amounts_pass() {
+        assert!(check_amount(1.5, &BitcoinUnit::BTC, 1.5).is_empty());
+        assert!(check_amount(0.1, &BitcoinUnit::SAT, 0.000_000_000_1).is_empty());
+        assert!(check_amount(12.345, &Fiat::USD, 0.000_2).is_empty());
+        assert!(check_amount(21_000_000.0, &BitcoinUnit::BTC, 21_000_000.0).is_empty());
+    }
+
+    #[test]
+    fn implausible_amounts_are_reported() {
+        assert_eq!(
+            check_amount(2.1e15, &BitcoinUnit::BTC, 2.1e15),
+            vec!["2100000000000000 BTC is more than the 21 million that will ever exist"]
+        );
+        assert_eq!(
+            check_amount(2.2e15, &BitcoinUnit::SAT, 2.2e7),
+            vec!["2200000000000000 SAT is 22000000 BTC, more than the 21 million that will ever exist"]
+        );
+        assert_eq!(
+            check_amount(-5.0, &BitcoinUnit::SAT, -0.000_000_05),
+            vec!["-5 SAT is negative"]
+        );
+        assert_eq!(
+            check_amount(1.5, &BitcoinUnit::MSAT, 0.000_000_000_015),
+            vec!["1.5 MSAT is more precise than MSAT supports (0 decimal places)"]
+        );
+        assert_eq!(
+            check_amount(f64::NAN, &Fiat::USD, f64::NAN),
+            vec!["NaN USD is not a finite amount"]
+        );
+        assert_eq!(
+            check_amount(100.0, &Fiat::USD, f64::INFINITY),
+            vec!["100 USD converts to inf BTC, the exchange rate may be zero"]
+        );
+    }
+}
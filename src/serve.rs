@@ -0,0 +1,275 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::panic::{self, AssertUnwindSafe};
+use std::time::{Duration, Instant};
+
+use serde_json::{json, Map, Value};
+use strum::IntoEnumIterator;
+use tiny_http::{Header, Method, Response, Server};
+use url::Url;
+
+use bitcoinvert::{check_quoted, BitcoinUnit, Commodity, Converter, Currency, ExchangeRates, Fiat};
+
+use crate::cli_input::CliInput;
+
+pub fn run(
+    listen: &str,
+    cache_ttl: Duration,
+    rates: &dyn ExchangeRates,
+) -> Result<(), Box<dyn Error>> {
+    let server = Server::http(listen).map_err(|e| format!("Unable to listen on {listen}: {e}"))?;
+    eprintln!("Listening on http://{listen}");
+
+    let mut last_refresh = Instant::now();
+
+    for request in server.incoming_requests() {
+        // All requests share the cached exchange rates until they expire.
+        if last_refresh.elapsed() >= cache_ttl {
+            rates.clear_cache();
+            last_refresh = Instant::now();
+        }
+
+        let (status, body) = if *request.method() == Method::Get {
+            handle_safely(request.url(), rates)
+        } else {
+            (405, error("Only GET requests are supported"))
+        };
+        log::debug!("{} {} -> {}", request.method(), request.url(), status);
+
+        let response = Response::from_string(body.to_string())
+            .with_status_code(status)
+            .with_header(
+                Header::from_bytes("Content-Type", "application/json")
+                    .expect("Invalid Content-Type header"),
+            );
+
+        if let Err(e) = request.respond(response) {
+            log::warn!("Failed to send response: {e}");
+        }
+    }
+
+    Ok(())
+}
+
+/// Answers with an internal error instead of stopping the server when a rate lookup panics, e.g.
+/// because the exchange rates couldn't be fetched.
+fn handle_safely(path_and_query: &str, rates: &dyn ExchangeRates) -> (u16, Value) {
+    panic::catch_unwind(AssertUnwindSafe(|| handle(path_and_query, rates))).unwrap_or_else(
+        |panic| {
+            let message = panic
+                .downcast_ref::<String>()
+                .map(String::as_str)
+                .or_else(|| panic.downcast_ref::<&str>().copied())
+                .unwrap_or("Internal error");
+            (500, error(message))
+        },
+    )
+}
+
+fn handle(path_and_query: &str, rates: &dyn ExchangeRates) -> (u16, Value) {
+    let url = match Url::parse(&format!("http://localhost{path_and_query}")) {
+        Ok(url) => url,
+        Err(e) => return (400, error(&e.to_string())),
+    };
+    let query: HashMap<String, String> = url.query_pairs().into_owned().collect();
+
+    match url.path() {
+        "/convert" => match convert(&query, rates) {
+            Ok(conversion) => conversion,
+            Err(e) => (400, error(&e)),
+        },
+        "/rates" => (200, btc_prices(rates)),
+        "/currencies" => (200, currencies()),
+        _ => (404, error("Not found")),
+    }
+}
+
+fn convert(
+    query: &HashMap<String, String>,
+    rates: &dyn ExchangeRates,
+) -> Result<(u16, Value), String> {
+    let param = |name: &str| {
+        query
+            .get(name)
+            .ok_or(format!("Missing query parameter \"{name}\""))
+    };
+
+    let amount =
+        CliInput::parse_amount(Some(param("amount")?.clone())).map_err(|e| e.to_string())?;
+    let from = parse_currency(param("from")?)?;
+    let to = parse_currency(param("to")?)?;
+
+    // A currency without a rate is a valid request that just can't be served
+    for currency in [&from, &to] {
+        if let Err(e) = check_quoted(&**currency, rates) {
+            return Ok((503, error(&e.to_string())));
+        }
+    }
+
+    let conversion = Converter::new(rates).convert(amount, &*from, &*to);
+
+    Ok((
+        200,
+        json!({
+            "amount": conversion.amount,
+            "from": conversion.from,
+            "to": conversion.to,
+            "result": conversion.value,
+            "rate": conversion.rate,
+        }),
+    ))
+}
+
+fn parse_currency(currency: &str) -> Result<Box<dyn Currency>, String> {
+    CliInput::parse_currency(currency).ok_or(format!("\"{currency}\" is not a valid currency!"))
+}
+
+// Price of 1 BTC in every fiat currency
+fn btc_prices(rates: &dyn ExchangeRates) -> Value {
+    let prices: Map<String, Value> = Fiat::iter()
+        .filter(|fiat| rates.is_quoted(fiat))
+        .map(|fiat| (fiat.to_string(), json!(1.0 / rates.btc_value(&fiat))))
+        .collect();
+
+    Value::Object(prices)
+}
+
+fn currencies() -> Value {
+    json!({
+        "bitcoin": BitcoinUnit::iter().map(|unit| unit.to_string()).collect::<Vec<_>>(),
+        "fiat": Fiat::iter().map(|fiat| fiat.to_string()).collect::<Vec<_>>(),
+        "commodities": Commodity::iter().map(|commodity| commodity.to_string()).collect::<Vec<_>>(),
+    })
+}
+
+fn error(message: &str) -> Value {
+    json!({ "error": message })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Mutex;
+
+    use bitcoinvert::{ExchangeRateApiConsumer, ExchangeRateProvider};
+
+    use super::*;
+
+    fn rates() -> HashMap<Fiat, f64> {
+        Fiat::iter().map(|fiat| (fiat, 50_000.0)).collect()
+    }
+
+    #[test]
+    fn converts_amount() {
+        let (status, body) = handle("/convert?amount=1.5k&from=sat&to=msat", &rates());
+
+        assert_eq!(status, 200);
+        assert_eq!(body["amount"], json!(1500.0));
+        assert_eq!(body["from"], json!("SAT"));
+        assert_eq!(body["to"], json!("MSAT"));
+        assert_eq!(body["result"], json!(1_500_000.0));
+        assert!((body["rate"].as_f64().unwrap() - 1000.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn decodes_query_parameters() {
+        let (status, body) = handle("/convert?amount=1%27000+000&from=SAT&to=BTC", &rates());
+
+        assert_eq!(status, 200);
+        assert_eq!(body["result"], json!(0.01));
+    }
+
+    #[test]
+    fn invalid_conversions_are_bad_requests() {
+        let (status, body) = handle("/convert?amount=1&from=SAT", &rates());
+        assert_eq!(status, 400);
+        assert_eq!(body, error("Missing query parameter \"to\""));
+
+        let (status, body) = handle("/convert?amount=1&from=SAT&to=XYZ", &rates());
+        assert_eq!(status, 400);
+        assert_eq!(body, error("\"XYZ\" is not a valid currency!"));
+
+        let (status, body) = handle("/convert?amount=one&from=SAT&to=BTC", &rates());
+        assert_eq!(status, 400);
+        assert_eq!(body, error("\"one\" is not a valid amount!"));
+    }
+
+    #[test]
+    fn converts_to_fiat() {
+        let (status, body) = handle("/convert?amount=2k&from=sat&to=usd", &rates());
+
+        assert_eq!(status, 200);
+        assert_eq!(body["result"], json!(1.0));
+    }
+
+    #[test]
+    fn lists_btc_prices() {
+        let (status, body) = handle("/rates", &rates());
+
+        assert_eq!(status, 200);
+        assert!((body["USD"].as_f64().unwrap() - 50_000.0).abs() < 1e-6);
+        assert_eq!(body.as_object().unwrap().len(), Fiat::iter().count());
+    }
+
+    #[test]
+    fn skips_currencies_without_price() {
+        let rates = HashMap::from([(Fiat::USD, 50_000.0)]);
+        let (status, body) = handle("/rates", &rates);
+
+        assert_eq!(status, 200);
+        assert_eq!(
+            body.as_object().unwrap().keys().collect::<Vec<_>>(),
+            ["USD"]
+        );
+    }
+
+    #[test]
+    fn lists_currencies() {
+        let (status, body) = handle("/currencies", &rates());
+
+        assert_eq!(status, 200);
+        assert_eq!(
+            body["bitcoin"],
+            json!(["BTC", "DBTC", "CBTC", "MBTC", "KSAT", "BITS", "µBTC", "SAT", "MSAT"])
+        );
+        assert!(body["fiat"].as_array().unwrap().contains(&json!("USD")));
+    }
+
+    #[test]
+    fn currency_without_rate_is_unavailable() {
+        let rates = HashMap::from([(Fiat::USD, 50_000.0)]);
+        let (status, body) = handle("/convert?amount=1&from=btc&to=mxn", &rates);
+
+        assert_eq!(status, 503);
+        assert_eq!(body, error("No exchange rate available for MXN"));
+    }
+
+    #[test]
+    fn failed_rate_lookup_is_an_internal_error() {
+        struct FlakyApi(AtomicUsize);
+
+        impl ExchangeRateApiConsumer for FlakyApi {
+            fn fetch_api(&self) -> HashMap<Fiat, f64> {
+                if self.0.fetch_add(1, Ordering::SeqCst) == 0 {
+                    panic!("Unable to request data!");
+                }
+                HashMap::from([(Fiat::USD, 50_000.0)])
+            }
+        }
+
+        let rates = Mutex::new(ExchangeRateProvider::new(FlakyApi(AtomicUsize::new(0))));
+
+        let (status, body) = handle_safely("/convert?amount=1&from=btc&to=usd", &rates);
+        assert_eq!(status, 500);
+        assert_eq!(body, error("Unable to request data!"));
+
+        let (status, body) = handle_safely("/convert?amount=1&from=btc&to=usd", &rates);
+        assert_eq!(status, 200);
+        assert_eq!(body["result"], json!(50_000.0));
+    }
+
+    #[test]
+    fn unknown_path_is_not_found() {
+        assert_eq!(handle("/unknown", &rates()).0, 404);
+    }
+}
@@ -0,0 +1,161 @@
+use std::error::Error;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use thousands::Separable;
+
+use bitcoinvert::issuance;
+use bitcoinvert::{BitcoinUnit, ExchangeRates};
+
+use crate::cli_input::SubsidyArgs;
+use crate::{fee, print};
+
+const SECONDS_PER_DAY: i64 = 86_400;
+
+/// Prints the block reward at a height or date, the next halving and the supply issued so far,
+/// followed by the value of the block reward in every requested or configured output currency.
+pub fn run(args: &SubsidyArgs, rates: &dyn ExchangeRates) -> Result<(), Box<dyn Error>> {
+    let (height, estimated) = match args.at.as_deref().map(str::trim) {
+        Some(at) if at.chars().all(|c| c.is_ascii_digit()) => (at.parse()?, false),
+        Some(at) => (issuance::estimate_height(parse_date(at)?), true),
+        None => (
+            issuance::estimate_height(SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs()),
+            true,
+        ),
+    };
+
+    let sat_value = BitcoinUnit::SAT.btc_value();
+    let subsidy = issuance::block_subsidy(height) as f64 * sat_value;
+    let supply = issuance::issued_supply(height) as f64 * sat_value;
+
+    println!(
+        "Block height: {}{}",
+        height.separate_with_commas(),
+        if estimated { " (estimated)" } else { "" }
+    );
+    println!("Block reward: {} BTC", subsidy.separate_with_commas());
+    if subsidy > 0.0 {
+        let next_halving = issuance::next_halving(height);
+        println!(
+            "Next halving: block {}, in {} blocks, around {}",
+            next_halving.separate_with_commas(),
+            (next_halving - height).separate_with_commas(),
+            format_date(issuance::estimate_time(next_halving))
+        );
+    } else {
+        println!("Next halving: none, the block reward has run out");
+    }
+    println!(
+        "Issued supply: {} BTC ({:.2}% of 21 million)\n",
+        supply.separate_with_commas(),
+        supply / issuance::MAX_SUPPLY_BTC * 100.0
+    );
+
+    print::multi_line(
+        subsidy,
+        &fee::output_currencies(&args.currencies, rates)?,
+        rates,
+        false,
+        None,
+    );
+
+    Ok(())
+}
+
+/// Parses a `YYYY-MM-DD` date into the UNIX timestamp of its midnight in UTC.
+fn parse_date(input: &str) -> Result<u64, String> {
+    let invalid = || format!("\"{input}\" is neither a block height nor a date like 2028-04-15");
+
+    let mut parts = input.splitn(3, '-').map(str::parse::<i64>);
+    let (Some(Ok(year)), Some(Ok(month)), Some(Ok(day))) =
+        (parts.next(), parts.next(), parts.next())
+    else {
+        return Err(invalid());
+    };
+    if !(1..=12).contains(&month) || !(1..=days_in_month(year, month)).contains(&day) {
+        return Err(invalid());
+    }
+
+    let unix_time = days_from_civil(year, month, day)
+        .and_then(|days| days.checked_mul(SECONDS_PER_DAY))
+        .ok_or(format!("{input} is too far in the future"))?;
+    u64::try_from(unix_time).map_err(|_| format!("{input} is before 1970"))
+}
+
+fn days_in_month(year: i64, month: i64) -> i64 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Formats a UNIX timestamp as a `YYYY-MM-DD` date in UTC.
+fn format_date(unix_time: u64) -> String {
+    let (year, month, day) = civil_from_days(unix_time as i64 / SECONDS_PER_DAY);
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+// Days since 1970-01-01 in the proleptic Gregorian calendar, after Howard Hinnant's algorithm
+fn days_from_civil(year: i64, month: i64, day: i64) -> Option<i64> {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+    era.checked_mul(146_097)?.checked_add(day_of_era - 719_468)
+}
+
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_dates() {
+        assert_eq!(parse_date("1970-01-01"), Ok(0));
+        assert_eq!(parse_date("2024-04-20"), Ok(1_713_571_200));
+        assert!(parse_date("2024-13-01").is_err());
+        assert!(parse_date("20.4.2024").is_err());
+        assert!(parse_date("1969-12-31").is_err());
+    }
+
+    #[test]
+    fn dates_must_exist() {
+        assert_eq!(parse_date("2024-02-29"), Ok(1_709_164_800));
+        assert!(parse_date("2023-02-29").is_err());
+        assert!(parse_date("2024-02-31").is_err());
+        assert!(parse_date("2024-04-31").is_err());
+        assert!(parse_date("2100-02-29").is_err());
+        assert_eq!(
+            parse_date("9223372036854775807-01-01"),
+            Err("9223372036854775807-01-01 is too far in the future".to_string())
+        );
+    }
+
+    #[test]
+    fn formats_dates() {
+        assert_eq!(format_date(0), "1970-01-01");
+        assert_eq!(format_date(1_713_571_767), "2024-04-20");
+        assert_eq!(format_date(1_709_164_800), "2024-02-29");
+    }
+}
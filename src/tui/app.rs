@@ -0,0 +1,416 @@
+use std::collections::{HashMap, VecDeque};
+
+use bitcoinvert::{check_quoted, Currency, ExchangeRates};
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use crate::cli_input::CliInput;
+use crate::print;
+
+// Number of rate refreshes kept for the sparkline
+const HISTORY_LENGTH: usize = 120;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    Amount,
+    InputCurrency,
+    NewCurrency,
+}
+
+impl Field {
+    fn next(self) -> Self {
+        match self {
+            Field::Amount => Field::InputCurrency,
+            Field::InputCurrency => Field::NewCurrency,
+            Field::NewCurrency => Field::Amount,
+        }
+    }
+
+    fn previous(self) -> Self {
+        match self {
+            Field::Amount => Field::NewCurrency,
+            Field::InputCurrency => Field::Amount,
+            Field::NewCurrency => Field::InputCurrency,
+        }
+    }
+}
+
+pub struct App<'a> {
+    pub amount: String,
+    pub input_currency: String,
+    pub new_currency: String,
+    pub focus: Field,
+    pub output_currencies: Vec<Box<dyn Currency>>,
+    pub selected: usize,
+    pub status: Option<String>,
+    pub should_quit: bool,
+    // Named exchange rate sources to switch between, and the one in use
+    sources: Vec<(String, &'a dyn ExchangeRates)>,
+    source: usize,
+    // Price of 1 BTC in every output currency, one entry per refresh
+    history: HashMap<String, VecDeque<f64>>,
+}
+
+impl<'a> App<'a> {
+    pub fn new(
+        amount: f64,
+        input_currency: &dyn Currency,
+        mut output_currencies: Vec<Box<dyn Currency>>,
+        sources: Vec<(String, &'a dyn ExchangeRates)>,
+    ) -> Self {
+        let rates = sources[0].1;
+        // Currencies without a rate can't be shown, so they are reported instead
+        let mut missing = Vec::new();
+        output_currencies.retain(|currency| match check_quoted(&**currency, rates) {
+            Ok(()) => true,
+            Err(e) => {
+                missing.push(e.to_string());
+                false
+            }
+        });
+
+        let mut app = Self {
+            amount: amount.to_string(),
+            input_currency: input_currency.to_string(),
+            new_currency: String::new(),
+            focus: Field::Amount,
+            output_currencies,
+            selected: 0,
+            status: (!missing.is_empty()).then(|| format!("{}!", missing.join(", "))),
+            should_quit: false,
+            sources,
+            source: 0,
+            history: HashMap::new(),
+        };
+        app.record_rates();
+
+        app
+    }
+
+    /// Converts the entered amount into every output currency.
+    pub fn output_values(&self) -> Result<Vec<f64>, String> {
+        let amount = CliInput::parse_amount(Some(self.amount.clone()))
+            .map_err(|_| format!("\"{}\" is not a valid amount!", self.amount))?;
+        let input_currency = CliInput::parse_currency(&self.input_currency).ok_or_else(|| {
+            format!(
+                "\"{}\" is not a valid (input) currency!",
+                self.input_currency
+            )
+        })?;
+        let rates = self.rates();
+        for currency in std::iter::once(&input_currency).chain(&self.output_currencies) {
+            check_quoted(&**currency, rates).map_err(|e| format!("{e}!"))?;
+        }
+
+        let value_in_btc = amount * input_currency.btc_value(rates);
+
+        Ok(self
+            .output_currencies
+            .iter()
+            .map(|currency| print::output_value(value_in_btc, &**currency, rates, false))
+            .collect())
+    }
+
+    pub fn rates(&self) -> &'a dyn ExchangeRates {
+        self.sources[self.source].1
+    }
+
+    pub fn source_name(&self) -> &str {
+        &self.sources[self.source].0
+    }
+
+    pub fn selected_currency(&self) -> Option<&dyn Currency> {
+        self.output_currencies.get(self.selected).map(|c| &**c)
+    }
+
+    /// Rate history of the selected currency, scaled to fit a sparkline.
+    pub fn sparkline_data(&self) -> Vec<u64> {
+        let Some(history) = self
+            .selected_currency()
+            .and_then(|currency| self.history.get(&currency.to_string()))
+        else {
+            return Vec::new();
+        };
+
+        let min = history.iter().copied().fold(f64::INFINITY, f64::min);
+        let max = history.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+
+        history
+            .iter()
+            .map(|rate| {
+                if max > min {
+                    ((rate - min) / (max - min) * 100.0).round() as u64 + 1
+                } else {
+                    1
+                }
+            })
+            .collect()
+    }
+
+    pub fn refresh_rates(&mut self) {
+        self.rates().clear_cache();
+        self.record_rates();
+    }
+
+    /// Switches to the next exchange rate source. The history of the previous one isn't
+    /// comparable, so it is dropped.
+    fn switch_source(&mut self) {
+        if self.sources.len() < 2 {
+            self.status = Some("No other exchange rate source configured.".to_string());
+            return;
+        }
+
+        self.source = (self.source + 1) % self.sources.len();
+        self.history.clear();
+        self.record_rates();
+        self.status = None;
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+
+        match key.code {
+            KeyCode::Esc => self.should_quit = true,
+            KeyCode::Char('c') if ctrl => self.should_quit = true,
+            KeyCode::Char('r') if ctrl => self.refresh_rates(),
+            KeyCode::Char('p') if ctrl => self.switch_source(),
+            KeyCode::Tab => self.focus = self.focus.next(),
+            KeyCode::BackTab => self.focus = self.focus.previous(),
+            KeyCode::Up => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Down if self.selected + 1 < self.output_currencies.len() => self.selected += 1,
+            KeyCode::Delete => self.remove_selected_currency(),
+            KeyCode::Enter if self.focus == Field::NewCurrency => self.add_currency(),
+            KeyCode::Backspace => {
+                self.focused_field().pop();
+            }
+            KeyCode::Char(c) if !ctrl => self.focused_field().push(c),
+            _ => {}
+        }
+    }
+
+    fn focused_field(&mut self) -> &mut String {
+        match self.focus {
+            Field::Amount => &mut self.amount,
+            Field::InputCurrency => &mut self.input_currency,
+            Field::NewCurrency => &mut self.new_currency,
+        }
+    }
+
+    fn add_currency(&mut self) {
+        let currency = match CliInput::parse_currency(self.new_currency.trim()) {
+            Some(currency) => currency,
+            None => {
+                self.status = Some(format!(
+                    "\"{}\" is not a valid currency!",
+                    self.new_currency
+                ));
+                return;
+            }
+        };
+
+        if self
+            .output_currencies
+            .iter()
+            .any(|c| c.to_string() == currency.to_string())
+        {
+            self.status = Some(format!("{currency} is already shown."));
+            return;
+        }
+        if let Err(e) = check_quoted(&*currency, self.rates()) {
+            self.status = Some(format!("{e}!"));
+            return;
+        }
+
+        self.record_rate(&*currency);
+        self.output_currencies.push(currency);
+        self.selected = self.output_currencies.len() - 1;
+        self.new_currency.clear();
+        self.status = None;
+    }
+
+    fn remove_selected_currency(&mut self) {
+        if self.selected < self.output_currencies.len() {
+            self.output_currencies.remove(self.selected);
+            self.selected = self
+                .selected
+                .min(self.output_currencies.len().saturating_sub(1));
+        }
+    }
+
+    fn record_rates(&mut self) {
+        let rates: Vec<(String, f64)> = self
+            .output_currencies
+            .iter()
+            // Not every source has a rate for every currency
+            .filter(|currency| check_quoted(&***currency, self.rates()).is_ok())
+            .map(|currency| {
+                let rate = 1.0 / currency.btc_value(self.rates());
+                (currency.to_string(), rate)
+            })
+            .collect();
+
+        for (unit, rate) in rates {
+            self.push_history(unit, rate);
+        }
+    }
+
+    fn record_rate(&mut self, currency: &dyn Currency) {
+        let rate = 1.0 / currency.btc_value(self.rates());
+        self.push_history(currency.to_string(), rate);
+    }
+
+    fn push_history(&mut self, unit: String, rate: f64) {
+        let history = self.history.entry(unit).or_default();
+        history.push_back(rate);
+        if history.len() > HISTORY_LENGTH {
+            history.pop_front();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bitcoinvert::{BitcoinUnit, Fiat};
+    use ratatui::crossterm::event::KeyEvent;
+
+    fn app(rates: &HashMap<Fiat, f64>) -> App<'_> {
+        App::new(
+            1.0,
+            &BitcoinUnit::BTC,
+            vec![Box::new(BitcoinUnit::SAT), Box::new(BitcoinUnit::MSAT)],
+            vec![("test".to_string(), rates)],
+        )
+    }
+
+    fn type_text(app: &mut App, text: &str) {
+        for c in text.chars() {
+            app.handle_key(KeyEvent::from(KeyCode::Char(c)));
+        }
+    }
+
+    #[test]
+    fn converts_edited_amount() {
+        let rates = HashMap::new();
+        let mut app = app(&rates);
+        app.handle_key(KeyEvent::from(KeyCode::Backspace));
+        type_text(&mut app, "2k");
+
+        assert_eq!(app.amount, "2k");
+        assert_eq!(
+            app.output_values(),
+            Ok(vec![200_000_000_000.0, 200_000_000_000_000.0])
+        );
+    }
+
+    #[test]
+    fn invalid_amount_is_reported() {
+        let rates = HashMap::new();
+        let mut app = app(&rates);
+        app.amount.clear();
+        type_text(&mut app, "abc");
+
+        assert_eq!(
+            app.output_values(),
+            Err("\"abc\" is not a valid amount!".to_string())
+        );
+    }
+
+    #[test]
+    fn add_and_remove_currencies() {
+        let rates = HashMap::from([(Fiat::USD, 50_000.0)]);
+        let mut app = app(&rates);
+        app.handle_key(KeyEvent::from(KeyCode::Tab));
+        app.handle_key(KeyEvent::from(KeyCode::Tab));
+        assert_eq!(app.focus, Field::NewCurrency);
+
+        type_text(&mut app, "usd");
+        app.handle_key(KeyEvent::from(KeyCode::Enter));
+        assert_eq!(app.output_currencies.len(), 3);
+        assert_eq!(app.selected_currency().unwrap().to_string(), "USD");
+        assert!(app.new_currency.is_empty());
+        assert_eq!(
+            app.output_values(),
+            Ok(vec![100_000_000.0, 100_000_000_000.0, 50_000.0])
+        );
+
+        type_text(&mut app, "sat");
+        app.handle_key(KeyEvent::from(KeyCode::Enter));
+        assert_eq!(app.output_currencies.len(), 3);
+        assert_eq!(app.status, Some("SAT is already shown.".to_string()));
+
+        app.handle_key(KeyEvent::from(KeyCode::Up));
+        app.handle_key(KeyEvent::from(KeyCode::Delete));
+        assert_eq!(
+            app.output_currencies
+                .iter()
+                .map(|c| c.to_string())
+                .collect::<Vec<_>>(),
+            vec!["SAT", "USD"]
+        );
+    }
+
+    #[test]
+    fn currencies_without_rate_are_reported() {
+        let rates = HashMap::from([(Fiat::USD, 50_000.0)]);
+        let mut app = App::new(
+            1.0,
+            &BitcoinUnit::BTC,
+            vec![Box::new(Fiat::USD), Box::new(Fiat::MXN)],
+            vec![("test".to_string(), &rates)],
+        );
+        assert_eq!(app.output_currencies.len(), 1);
+        assert_eq!(
+            app.status,
+            Some("No exchange rate available for MXN!".to_string())
+        );
+
+        app.new_currency = "zar".to_string();
+        app.focus = Field::NewCurrency;
+        app.handle_key(KeyEvent::from(KeyCode::Enter));
+        assert_eq!(app.output_currencies.len(), 1);
+        assert_eq!(
+            app.status,
+            Some("No exchange rate available for ZAR!".to_string())
+        );
+
+        app.input_currency = "php".to_string();
+        assert_eq!(
+            app.output_values(),
+            Err("No exchange rate available for PHP!".to_string())
+        );
+    }
+
+    #[test]
+    fn switches_rate_sources() {
+        let api = HashMap::from([(Fiat::USD, 50_000.0)]);
+        let ecb = HashMap::from([(Fiat::USD, 51_000.0), (Fiat::MXN, 900_000.0)]);
+        let mut app = App::new(
+            1.0,
+            &BitcoinUnit::BTC,
+            vec![Box::new(Fiat::USD), Box::new(Fiat::MXN)],
+            vec![("ECB".to_string(), &ecb), ("API".to_string(), &api)],
+        );
+        assert_eq!(app.output_values(), Ok(vec![51_000.0, 900_000.0]));
+
+        let ctrl_p = KeyEvent::new(KeyCode::Char('p'), KeyModifiers::CONTROL);
+        app.handle_key(ctrl_p);
+        assert_eq!(app.source_name(), "API");
+        assert_eq!(
+            app.output_values(),
+            Err("No exchange rate available for MXN!".to_string())
+        );
+        assert_eq!(app.sparkline_data().len(), 1);
+
+        app.handle_key(ctrl_p);
+        assert_eq!(app.source_name(), "ECB");
+        assert_eq!(app.output_values(), Ok(vec![51_000.0, 900_000.0]));
+    }
+
+    #[test]
+    fn quits_on_escape() {
+        let rates = HashMap::new();
+        let mut app = app(&rates);
+        app.handle_key(KeyEvent::from(KeyCode::Esc));
+        assert!(app.should_quit);
+    }
+}
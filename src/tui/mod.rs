@@ -0,0 +1,54 @@
+mod app;
+mod ui;
+
+use std::error::Error;
+use std::io;
+use std::time::{Duration, Instant};
+
+use bitcoinvert::ExchangeRates;
+use ratatui::crossterm::event::{self, Event};
+use ratatui::DefaultTerminal;
+
+use crate::defaults::Defaults;
+use crate::tui::app::App;
+
+const REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Runs the dashboard, converting with the first of the named exchange rate `sources` until
+/// another one is picked.
+pub fn run(sources: Vec<(String, &dyn ExchangeRates)>) -> Result<(), Box<dyn Error>> {
+    let mut app = App::new(
+        Defaults::get_default_amount()?,
+        &*Defaults::get_default_input_currency()?,
+        Defaults::get_default_output_currencies()?,
+        sources,
+    );
+
+    ratatui::run(|terminal| event_loop(terminal, &mut app))?;
+
+    Ok(())
+}
+
+fn event_loop(terminal: &mut DefaultTerminal, app: &mut App) -> io::Result<()> {
+    let mut last_refresh = Instant::now();
+
+    while !app.should_quit {
+        terminal.draw(|frame| ui::draw(frame, app))?;
+
+        let timeout = REFRESH_INTERVAL.saturating_sub(last_refresh.elapsed());
+        if event::poll(timeout)? {
+            if let Event::Key(key) = event::read()? {
+                if key.is_press() {
+                    app.handle_key(key);
+                }
+            }
+        }
+
+        if last_refresh.elapsed() >= REFRESH_INTERVAL {
+            app.refresh_rates();
+            last_refresh = Instant::now();
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,131 @@
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::widgets::{Block, Paragraph, Row, Sparkline, Table, TableState};
+use ratatui::Frame;
+use thousands::Separable;
+
+use crate::tui::app::{App, Field};
+
+const HELP: &str =
+    "Tab: next field | Up/Down: select | Enter: add currency | Del: remove | Ctrl+R: refresh | Ctrl+P: switch provider | Esc: quit";
+
+pub fn draw(frame: &mut Frame, app: &App) {
+    let [inputs, table, sparkline, footer] = Layout::vertical([
+        Constraint::Length(3),
+        Constraint::Min(5),
+        Constraint::Length(7),
+        Constraint::Length(1),
+    ])
+    .areas(frame.area());
+
+    draw_inputs(frame, app, inputs);
+    draw_table(frame, app, table);
+    draw_sparkline(frame, app, sparkline);
+
+    let footer_text = match &app.status {
+        Some(status) => Paragraph::new(status.as_str()).style(Style::new().fg(Color::Red)),
+        None => Paragraph::new(HELP).style(Style::new().add_modifier(Modifier::DIM)),
+    };
+    frame.render_widget(footer_text, footer);
+}
+
+fn draw_inputs(frame: &mut Frame, app: &App, area: Rect) {
+    let areas: [Rect; 3] = Layout::horizontal([Constraint::Fill(1); 3]).areas(area);
+    let fields = [
+        (Field::Amount, "Amount", &app.amount),
+        (Field::InputCurrency, "From", &app.input_currency),
+        (Field::NewCurrency, "Add currency", &app.new_currency),
+    ];
+
+    for ((field, title, text), area) in fields.into_iter().zip(areas) {
+        let mut block = Block::bordered().title(title);
+        if app.focus == field {
+            block = block.border_style(Style::new().fg(Color::Yellow));
+        }
+
+        frame.render_widget(Paragraph::new(text.as_str()).block(block), area);
+    }
+}
+
+fn draw_table(frame: &mut Frame, app: &App, area: Rect) {
+    let (output_values, title) = match app.output_values() {
+        Ok(values) => (values.into_iter().map(Some).collect(), String::new()),
+        Err(e) => (vec![None; app.output_currencies.len()], e),
+    };
+
+    let rows = app
+        .output_currencies
+        .iter()
+        .zip(output_values)
+        .map(|(currency, value)| {
+            Row::new([
+                currency.to_string(),
+                value.map_or("-".to_string(), |value| value.separate_with_commas()),
+            ])
+        });
+
+    let table = Table::new(rows, [Constraint::Length(6), Constraint::Fill(1)])
+        .header(Row::new(["unit", "amount"]).style(Style::new().add_modifier(Modifier::BOLD)))
+        .row_highlight_style(Style::new().add_modifier(Modifier::REVERSED))
+        .block(
+            Block::bordered()
+                .title(title)
+                .title_style(Style::new().fg(Color::Red)),
+        );
+
+    let mut state = TableState::default().with_selected(Some(app.selected));
+    frame.render_stateful_widget(table, area, &mut state);
+}
+
+fn draw_sparkline(frame: &mut Frame, app: &App, area: Rect) {
+    let title = match app.selected_currency() {
+        Some(currency) => format!("1 BTC in {currency}, rates from {}", app.source_name()),
+        None => String::new(),
+    };
+
+    let sparkline = Sparkline::default()
+        .block(Block::bordered().title(title))
+        .data(app.sparkline_data())
+        .style(Style::new().fg(Color::Green));
+
+    frame.render_widget(sparkline, area);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bitcoinvert::{BitcoinUnit, Fiat};
+    use ratatui::backend::TestBackend;
+    use ratatui::Terminal;
+    use std::collections::HashMap;
+
+    #[test]
+    fn renders_conversion_table() {
+        let rates = HashMap::from([(Fiat::USD, 65_000.0)]);
+        let app = App::new(
+            1.0,
+            &BitcoinUnit::BTC,
+            vec![
+                Box::new(BitcoinUnit::SAT),
+                Box::new(BitcoinUnit::MSAT),
+                Box::new(Fiat::USD),
+            ],
+            vec![("rates.yaml".to_string(), &rates)],
+        );
+        let mut terminal = Terminal::new(TestBackend::new(100, 20)).unwrap();
+
+        terminal.draw(|frame| draw(frame, &app)).unwrap();
+
+        let screen: String = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect();
+        assert!(screen.contains("SAT   100,000,000"));
+        assert!(screen.contains("MSAT   100,000,000,000"));
+        assert!(screen.contains("USD    65,000"));
+        assert!(screen.contains("1 BTC in SAT, rates from rates.yaml"));
+    }
+}
@@ -0,0 +1,185 @@
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+// Version and lock time
+const TX_FIXED_BYTES: u64 = 8;
+// Segwit marker and flag, only counted in the witness
+const SEGWIT_MARKER_WEIGHT: u64 = 2;
+const WITNESS_SCALE_FACTOR: u64 = 4;
+// Previous outpoint, script length and sequence
+const INPUT_FIXED_BYTES: u64 = 36 + 1 + 4;
+// Value and script length
+const OUTPUT_FIXED_BYTES: u64 = 8 + 1;
+const SIGNATURE_BYTES: u64 = 72;
+const SCHNORR_SIGNATURE_BYTES: u64 = 64;
+const PUBKEY_BYTES: u64 = 33;
+const MAX_MULTISIG_KEYS: u8 = 20;
+
+/// Script types of transaction inputs and outputs, to estimate the size of a transaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScriptType {
+    P2PKH,
+    P2WPKH,
+    P2SHP2WPKH,
+    /// Key path spend
+    P2TR,
+    /// m-of-n multisig in P2WSH
+    Multisig {
+        m: u8,
+        n: u8,
+    },
+}
+
+#[derive(Debug, PartialEq, Eq, thiserror::Error)]
+#[error("{msg}")]
+pub struct ScriptTypeParseError {
+    msg: String,
+}
+
+impl FromStr for ScriptType {
+    type Err = ScriptTypeParseError;
+
+    /// Parses `p2pkh`, `p2wpkh`, `p2sh-p2wpkh`, `p2tr` or an m-of-n multisig like `2-of-3`.
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input.to_ascii_lowercase().as_str() {
+            "p2pkh" => Ok(ScriptType::P2PKH),
+            "p2wpkh" => Ok(ScriptType::P2WPKH),
+            "p2sh-p2wpkh" => Ok(ScriptType::P2SHP2WPKH),
+            "p2tr" => Ok(ScriptType::P2TR),
+            multisig => Self::parse_multisig(multisig).ok_or(ScriptTypeParseError {
+                msg: format!(
+                    "\"{input}\" is not a supported script type, use p2pkh, p2wpkh, p2sh-p2wpkh, p2tr or m-of-n"
+                ),
+            }),
+        }
+    }
+}
+
+impl Display for ScriptType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ScriptType::P2PKH => write!(f, "P2PKH"),
+            ScriptType::P2WPKH => write!(f, "P2WPKH"),
+            ScriptType::P2SHP2WPKH => write!(f, "P2SH-P2WPKH"),
+            ScriptType::P2TR => write!(f, "P2TR"),
+            ScriptType::Multisig { m, n } => write!(f, "{m}-of-{n} multisig"),
+        }
+    }
+}
+
+impl ScriptType {
+    fn parse_multisig(input: &str) -> Option<Self> {
+        let (m, n) = input.split_once("-of-")?;
+        let (m, n): (u8, u8) = (m.parse().ok()?, n.parse().ok()?);
+
+        (1 <= m && m <= n && n <= MAX_MULTISIG_KEYS).then_some(ScriptType::Multisig { m, n })
+    }
+
+    fn is_segwit(&self) -> bool {
+        !matches!(self, ScriptType::P2PKH)
+    }
+
+    /// Weight of an input spending this script
+    fn input_weight(&self) -> u64 {
+        let (script_sig_bytes, witness_bytes) = match self {
+            // Signature and public key, each with a push opcode
+            ScriptType::P2PKH => (1 + SIGNATURE_BYTES + 1 + PUBKEY_BYTES, 0),
+            // Item count, then signature and public key with their lengths
+            ScriptType::P2WPKH => (0, 1 + 1 + SIGNATURE_BYTES + 1 + PUBKEY_BYTES),
+            // Push of the P2WPKH redeem script
+            ScriptType::P2SHP2WPKH => (1 + 22, 1 + 1 + SIGNATURE_BYTES + 1 + PUBKEY_BYTES),
+            ScriptType::P2TR => (0, 1 + 1 + SCHNORR_SIGNATURE_BYTES),
+            ScriptType::Multisig { m, n } => {
+                let witness_script_bytes = multisig_script_bytes(*n);
+                // Item count, empty dummy item, signatures and witness script with their lengths
+                let witness_bytes = 1
+                    + 1
+                    + u64::from(*m) * (1 + SIGNATURE_BYTES)
+                    + var_int_bytes(witness_script_bytes)
+                    + witness_script_bytes;
+                (0, witness_bytes)
+            }
+        };
+
+        (INPUT_FIXED_BYTES + script_sig_bytes) * WITNESS_SCALE_FACTOR + witness_bytes
+    }
+
+    /// Weight of an output paying to this script
+    fn output_weight(&self) -> u64 {
+        let script_pubkey_bytes = match self {
+            ScriptType::P2PKH => 25,
+            ScriptType::P2WPKH => 22,
+            ScriptType::P2SHP2WPKH => 23,
+            ScriptType::P2TR | ScriptType::Multisig { .. } => 34,
+        };
+
+        (OUTPUT_FIXED_BYTES + script_pubkey_bytes) * WITNESS_SCALE_FACTOR
+    }
+}
+
+/// Estimated virtual size of a transaction in vbytes, assuming signatures of maximum size.
+pub fn estimate_vsize(inputs: &[ScriptType], outputs: &[ScriptType]) -> u64 {
+    let mut weight =
+        (TX_FIXED_BYTES + var_int_bytes(inputs.len() as u64) + var_int_bytes(outputs.len() as u64))
+            * WITNESS_SCALE_FACTOR;
+
+    if inputs.iter().any(ScriptType::is_segwit) {
+        weight += SEGWIT_MARKER_WEIGHT;
+        // Inputs without a witness still need an empty one
+        weight += inputs.iter().filter(|input| !input.is_segwit()).count() as u64;
+    }
+
+    weight += inputs.iter().map(ScriptType::input_weight).sum::<u64>();
+    weight += outputs.iter().map(ScriptType::output_weight).sum::<u64>();
+
+    weight.div_ceil(WITNESS_SCALE_FACTOR)
+}
+
+// OP_m, the public keys with their push opcodes, OP_n and OP_CHECKMULTISIG
+fn multisig_script_bytes(n: u8) -> u64 {
+    1 + u64::from(n) * (1 + PUBKEY_BYTES) + 1 + 1
+}
+
+fn var_int_bytes(value: u64) -> u64 {
+    match value {
+        0..=0xfc => 1,
+        0xfd..=0xffff => 3,
+        0x1_0000..=0xffff_ffff => 5,
+        _ => 9,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_script_types() {
+        assert_eq!(ScriptType::from_str("P2TR"), Ok(ScriptType::P2TR));
+        assert_eq!(
+            ScriptType::from_str("p2sh-p2wpkh"),
+            Ok(ScriptType::P2SHP2WPKH)
+        );
+        assert_eq!(
+            ScriptType::from_str("2-of-3"),
+            Ok(ScriptType::Multisig { m: 2, n: 3 })
+        );
+        assert!(ScriptType::from_str("3-of-2").is_err());
+        assert!(ScriptType::from_str("1-of-21").is_err());
+        assert!(ScriptType::from_str("p2sh").is_err());
+    }
+
+    #[test]
+    fn estimates_common_transactions() {
+        use ScriptType::*;
+
+        assert_eq!(estimate_vsize(&[P2WPKH], &[P2WPKH, P2WPKH]), 141);
+        assert_eq!(estimate_vsize(&[P2TR, P2TR], &[P2TR, P2TR]), 212);
+        assert_eq!(estimate_vsize(&[P2PKH], &[P2PKH, P2PKH]), 226);
+        assert_eq!(estimate_vsize(&[P2SHP2WPKH], &[P2WPKH, P2WPKH]), 164);
+        assert_eq!(
+            estimate_vsize(&[Multisig { m: 2, n: 3 }], &[P2WPKH, P2WPKH]),
+            177
+        );
+    }
+}
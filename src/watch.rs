@@ -0,0 +1,64 @@
+use std::thread;
+use std::time::Duration;
+
+use bitcoinvert::ExchangeRates;
+use colored::*;
+
+use crate::cli_input::CliInput;
+use crate::print;
+
+const CLEAR_SCREEN: &str = "\x1B[2J\x1B[1;1H";
+
+pub fn run(cli_input: &CliInput, interval: Duration, rates: &dyn ExchangeRates) -> ! {
+    let mut previous: Option<Vec<f64>> = None;
+
+    loop {
+        rates.clear_cache();
+        let value_in_btc = cli_input.amount * cli_input.input_currency.btc_value(rates);
+
+        if cli_input.output_currencies.len() == 1 {
+            let currency = &*cli_input.output_currencies[0];
+            let output_value =
+                print::output_value(value_in_btc, currency, rates, cli_input.integer);
+
+            // Clean output is meant to be piped, so every tick goes on a new line instead.
+            if !cli_input.clean {
+                clear_screen(interval);
+            }
+
+            print::single_line_with_trend(
+                output_value,
+                currency,
+                cli_input.clean,
+                cli_input.sat_grouping,
+                previous.as_ref().map(|previous| previous[0]),
+            );
+            previous = Some(vec![output_value]);
+        } else {
+            clear_screen(interval);
+
+            previous = Some(print::multi_line_with_trend(
+                value_in_btc,
+                &cli_input.output_currencies,
+                rates,
+                cli_input.integer,
+                cli_input.sat_grouping,
+                previous.as_deref(),
+            ));
+        }
+
+        thread::sleep(interval);
+    }
+}
+
+fn clear_screen(interval: Duration) {
+    print!("{CLEAR_SCREEN}");
+    println!(
+        "{}\n",
+        format!(
+            "Refreshing every {}s, press Ctrl+C to quit.",
+            interval.as_secs()
+        )
+        .dimmed()
+    );
+}
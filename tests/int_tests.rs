@@ -1,21 +1,28 @@
 use assert_cmd::cargo;
 
+// Fixed exchange rates, so the tests don't depend on the network (1 BTC = 65,000 USD)
+const RATES_FILE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/ticker.json");
+
 #[test]
 fn test_no_arguments() {
     let mut cmd = cargo::cargo_bin_cmd!("bitcoinvert");
-    cmd.assert().success();
+    cmd.args(["--rates-file", RATES_FILE]).assert().success();
 }
 
 #[test]
 fn test_one_argument() {
     let mut cmd = cargo::cargo_bin_cmd!("bitcoinvert");
-    cmd.args(["1"]).assert().success();
+    cmd.args(["--rates-file", RATES_FILE, "1"])
+        .assert()
+        .success();
 }
 
 #[test]
 fn test_two_arguments() {
     let mut cmd = cargo::cargo_bin_cmd!("bitcoinvert");
-    cmd.args(["1", "BTC"]).assert().success();
+    cmd.args(["--rates-file", RATES_FILE, "1", "BTC"])
+        .assert()
+        .success();
 }
 
 #[test]
@@ -26,6 +33,940 @@ fn test_three_arguments() {
         .stdout("100,000,000 SAT\n");
 }
 
+#[test]
+fn test_fiat_conversion() {
+    let mut cmd = cargo::cargo_bin_cmd!("bitcoinvert");
+    cmd.args(["--rates-file", RATES_FILE, "1k", "SAT", "USD"])
+        .assert()
+        .stdout("0.65 USD\n");
+
+    let mut cmd = cargo::cargo_bin_cmd!("bitcoinvert");
+    cmd.args(["--rates-file", RATES_FILE, "-c", "59", "EUR", "SAT"])
+        .assert()
+        .stdout("100000\n");
+}
+
+#[test]
+fn test_rate_override() {
+    // Overridden rates don't need to be fetched
+    let mut cmd = cargo::cargo_bin_cmd!("bitcoinvert");
+    cmd.args(["--rate", "usd=64.25k", "1k", "SAT", "USD"])
+        .assert()
+        .stdout("0.64 USD\n");
+
+    // Overrides take precedence over the rates file
+    let mut cmd = cargo::cargo_bin_cmd!("bitcoinvert");
+    cmd.args(["--rates-file", RATES_FILE, "--rate", "EUR=50000"])
+        .args(["--rate", "USD=40000", "-c", "100", "EUR", "USD"])
+        .assert()
+        .stdout("80\n");
+
+    for rate in ["USD", "USD=0", "USD=NaN"] {
+        let mut cmd = cargo::cargo_bin_cmd!("bitcoinvert");
+        cmd.args(["--rate", rate, "1", "BTC", "USD"])
+            .assert()
+            .failure()
+            .code(2);
+    }
+}
+
+#[test]
+fn test_ecb_rates() {
+    let ecb_rates = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/fixtures/eurofxref-daily.xml"
+    );
+
+    // MXN isn't in the ticker, its price is derived from the USD price
+    let mut cmd = cargo::cargo_bin_cmd!("bitcoinvert");
+    cmd.args([
+        "--rates-file",
+        RATES_FILE,
+        &format!("--ecb-rates={ecb_rates}"),
+    ])
+    .args(["1", "BTC", "MXN"])
+    .assert()
+    .stdout("1,107,392.09 MXN\n");
+
+    let mut cmd = cargo::cargo_bin_cmd!("bitcoinvert");
+    cmd.args([
+        "--rates-file",
+        RATES_FILE,
+        &format!("--ecb-rates={ecb_rates}"),
+    ])
+    .args(["--ecb-anchor", "eur", "1", "BTC", "MXN"])
+    .assert()
+    .stdout("1,089,806.7 MXN\n");
+
+    let mut cmd = cargo::cargo_bin_cmd!("bitcoinvert");
+    cmd.args([
+        "--rates-file",
+        RATES_FILE,
+        &format!("--ecb-rates={ecb_rates}"),
+    ])
+    .args(["--ecb-anchor", "ARS", "1", "BTC", "MXN"])
+    .assert()
+    .failure()
+    .stderr("The ECB reference rates don't include ARS\n");
+}
+
+#[test]
+fn test_precious_metals() {
+    // 1 BTC = 65,000 USD, 1 troy ounce of gold = 2,600 USD
+    let metal_prices = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/fixtures/metal_prices.yaml"
+    );
+
+    let mut cmd = cargo::cargo_bin_cmd!("bitcoinvert");
+    cmd.args(["--rates-file", RATES_FILE, "--metal-prices", metal_prices])
+        .args(["1", "BTC", "XAU"])
+        .assert()
+        .stdout("25 XAU\n");
+
+    let mut cmd = cargo::cargo_bin_cmd!("bitcoinvert");
+    cmd.args(["--rates-file", RATES_FILE, "--metal-prices", metal_prices])
+        .args(["1", "oz-gold", "sat"])
+        .assert()
+        .stdout("4,000,000 SAT\n");
+
+    let mut cmd = cargo::cargo_bin_cmd!("bitcoinvert");
+    cmd.args(["--rates-file", RATES_FILE, "--metal-prices", metal_prices])
+        .args(["1", "BTC", "g-gold"])
+        .assert()
+        .stdout("777.59 g-gold\n");
+}
+
+#[test]
+fn test_yaml_rates_file() {
+    let mut cmd = cargo::cargo_bin_cmd!("bitcoinvert");
+    let rates_file = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/rates.yaml");
+    cmd.args(["--rates-file", rates_file, "1", "BTC", "CHF"])
+        .assert()
+        .stdout("57,500.5 CHF\n");
+}
+
+#[test]
+fn test_cross_fiat_conversion() {
+    // Triangulated through the BTC prices (1 BTC = 59,000 EUR = 57,500 CHF)
+    let mut cmd = cargo::cargo_bin_cmd!("bitcoinvert");
+    let stderr = cmd
+        .args(["--rates-file", RATES_FILE, "100", "EUR", "CHF"])
+        .assert()
+        .stdout("97.46 CHF\n")
+        .get_output()
+        .stderr
+        .clone();
+    assert!(String::from_utf8(stderr)
+        .unwrap()
+        .contains("1 EUR = 0.9746 CHF (implied by the BTC prices)"));
+
+    // Converted with the FX rate, which differs by more than 1%
+    let mut cmd = cargo::cargo_bin_cmd!("bitcoinvert");
+    let fx_rates = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/fx_rates.yaml");
+    let stderr = cmd
+        .args(["--rates-file", RATES_FILE, "--fx-rates", fx_rates])
+        .args(["100", "EUR", "CHF"])
+        .assert()
+        .stdout("94.12 CHF\n")
+        .get_output()
+        .stderr
+        .clone();
+    let stderr = String::from_utf8(stderr).unwrap();
+    assert!(stderr.contains("1 EUR = 0.9412 CHF (FX rate)"));
+    assert!(stderr.contains("differs from the FX rate by +3.55%"));
+
+    // The FX rates would be ignored by the multi-currency table and the watch mode
+    let home = std::env::temp_dir().join("bitcoinvert_fx_rates_home");
+    let mut cmd = cargo::cargo_bin_cmd!("bitcoinvert");
+    cmd.env("HOME", &home)
+        .args([
+            "--rates-file",
+            RATES_FILE,
+            "--fx-rates",
+            fx_rates,
+            "100",
+            "EUR",
+        ])
+        .assert()
+        .failure()
+        .code(64)
+        .stderr("--fx-rates only applies to a conversion into a single currency!\n");
+
+    let mut cmd = cargo::cargo_bin_cmd!("bitcoinvert");
+    cmd.args(["--fx-rates", fx_rates, "--watch", "5", "100", "EUR", "CHF"])
+        .assert()
+        .failure()
+        .code(2);
+}
+
+#[test]
+fn test_missing_rates_file() {
+    let mut cmd = cargo::cargo_bin_cmd!("bitcoinvert");
+    let stderr = cmd
+        .args(["--rates-file", "does/not/exist.json", "1", "BTC", "USD"])
+        .assert()
+        .failure()
+        .get_output()
+        .stderr
+        .clone();
+
+    assert!(String::from_utf8(stderr)
+        .unwrap()
+        .starts_with("Unable to read rates file does/not/exist.json"));
+}
+
+#[test]
+fn test_bitcoin_denominations() {
+    let mut cmd = cargo::cargo_bin_cmd!("bitcoinvert");
+    cmd.args(["1", "cBTC", "uBTC"])
+        .assert()
+        .stdout("10,000 µBTC\n");
+
+    let mut cmd = cargo::cargo_bin_cmd!("bitcoinvert");
+    cmd.args(["21", "ksats", "bits"])
+        .assert()
+        .stdout("210 BITS\n");
+
+    let mut cmd = cargo::cargo_bin_cmd!("bitcoinvert");
+    cmd.args(["1", "Msat", "sat"]).assert().stderr(
+        "\"Msat\" is not a valid (input) currency! Use MSAT for milli-satoshis, or 1M SAT for a million satoshis.\n",
+    );
+}
+
+#[test]
+fn test_sat_grouping() {
+    let mut cmd = cargo::cargo_bin_cmd!("bitcoinvert");
+    cmd.args(["--group-sats", "1234567", "SAT", "BTC"])
+        .assert()
+        .stdout("0.01 234 567 BTC\n");
+
+    // The separator has to be given with "=", so the amount isn't taken for one
+    let mut cmd = cargo::cargo_bin_cmd!("bitcoinvert");
+    cmd.args(["--group-sats", "0.5", "BTC", "BTC"])
+        .assert()
+        .stdout("0.50 000 000 BTC\n");
+
+    let mut cmd = cargo::cargo_bin_cmd!("bitcoinvert");
+    cmd.args(["--group-sats=comma", "1234567", "SAT", "BTC"])
+        .assert()
+        .stdout("0.01,234,567 BTC\n");
+
+    // Clean output stays a plain number
+    let mut cmd = cargo::cargo_bin_cmd!("bitcoinvert");
+    cmd.args(["-c", "--group-sats=space", "1234567", "SAT", "BTC"])
+        .assert()
+        .stdout("0.01234567\n");
+}
+
+#[test]
+fn test_lightning_invoice() {
+    // 2500u from the BOLT11 specification
+    let invoice = "lnbc2500u1pvjluezsp5zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zygspp5qqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqypqdq5xysxxatsyp3k7enxv4jsxqzpu9qrsgquk0rl77nj30yxdy8j9vdx85fkpmdla2087ne0xh8nhedh8w27kyke0lp53ut353s06fv3qfegext0eh0ymjpf39tuven09sam30g4vgpfna3rh";
+
+    let mut cmd = cargo::cargo_bin_cmd!("bitcoinvert");
+    cmd.args(["-c", invoice, "sat"]).assert().stdout("250000\n");
+
+    let mut cmd = cargo::cargo_bin_cmd!("bitcoinvert");
+    let output = cmd
+        .args([invoice, "BTC"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.starts_with("Description: 1 cup coffee\nExpiry: 60 seconds, "));
+    assert!(output.ends_with("0.0025 BTC\n"));
+
+    let mut cmd = cargo::cargo_bin_cmd!("bitcoinvert");
+    cmd.args(["-c", &invoice.replacen("2500u", "2600u", 1), "sat"])
+        .assert()
+        .failure()
+        .code(64)
+        .stderr("Invalid Lightning invoice: the checksum is wrong\n");
+}
+
+#[test]
+fn test_bip21() {
+    let uri = "bitcoin:bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq?amount=0.0015&label=Shop";
+
+    let mut cmd = cargo::cargo_bin_cmd!("bitcoinvert");
+    cmd.args(["-c", uri, "sat"]).assert().stdout("150000\n");
+
+    let mut cmd = cargo::cargo_bin_cmd!("bitcoinvert");
+    cmd.args([uri, "sat"]).assert().stdout(
+        "Address: bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq\nLabel: Shop\n\n150,000 SAT\n",
+    );
+
+    // 65 USD at 65,000 USD per BTC is 0.001 BTC
+    let mut cmd = cargo::cargo_bin_cmd!("bitcoinvert");
+    cmd.args([
+        "65",
+        "usd",
+        "--bip21",
+        "bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq",
+    ])
+    .args(["--rate", "USD=65000"])
+    .assert()
+    .stdout("bitcoin:bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq?amount=0.001\n");
+}
+
+#[test]
+fn test_qr_code() {
+    let svg = std::env::temp_dir().join("bitcoinvert_bip21.svg");
+
+    let mut cmd = cargo::cargo_bin_cmd!("bitcoinvert");
+    let output = cmd
+        .args([
+            "65",
+            "usd",
+            "--bip21",
+            "bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq",
+            "--qr",
+        ])
+        .args(["--qr-file", svg.to_str().unwrap(), "--rate", "USD=65000"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let output = String::from_utf8(output).unwrap();
+
+    assert!(output.starts_with("bitcoin:bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq?amount=0.001\n"));
+    assert!(output.contains('▀'));
+    assert!(std::fs::read_to_string(&svg).unwrap().contains("<svg"));
+
+    // A QR code needs a URI to encode
+    let mut cmd = cargo::cargo_bin_cmd!("bitcoinvert");
+    cmd.args(["65", "usd", "--qr"]).assert().failure().code(2);
+}
+
+#[test]
+fn test_payment_request() {
+    let record = std::env::temp_dir().join("bitcoinvert_quotes.jsonl");
+    let _ = std::fs::remove_file(&record);
+
+    // 49.90 EUR at 50,000 EUR per BTC is 99,800 SAT
+    let mut cmd = cargo::cargo_bin_cmd!("bitcoinvert");
+    let output = cmd
+        .args([
+            "request",
+            "49.90",
+            "EUR",
+            "--address",
+            "bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq",
+        ])
+        .args([
+            "--round-to",
+            "1000",
+            "--label",
+            "Corner Shop",
+            "--valid-for",
+            "10",
+        ])
+        .args(["--record", record.to_str().unwrap(), "--rate", "EUR=50000"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let quote: serde_json::Value = serde_json::from_slice(&output).unwrap();
+
+    assert_eq!(quote["sats"], 100_000);
+    assert_eq!(quote["rate"], 50_000.0);
+    assert_eq!(quote["source"], "fixed rate");
+    assert_eq!(
+        quote["expires_at"].as_u64().unwrap() - quote["quoted_at"].as_u64().unwrap(),
+        600
+    );
+    assert_eq!(
+        quote["uri"],
+        "bitcoin:bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq?amount=0.001&label=Corner%20Shop"
+    );
+
+    let recorded: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&record).unwrap()).unwrap();
+    assert_eq!(recorded, quote);
+}
+
+#[test]
+fn test_payment_request_too_large() {
+    let address = "bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq";
+
+    let mut cmd = cargo::cargo_bin_cmd!("bitcoinvert");
+    cmd.args(["request", "100000T", "USD", "--address", address])
+        .args(["--round-to", "1000", "--rate", "USD=65000"])
+        .assert()
+        .failure()
+        .code(70)
+        .stderr(
+            "100000000000000000 USD is more than the 21 million bitcoin that will ever exist\n",
+        );
+
+    let mut cmd = cargo::cargo_bin_cmd!("bitcoinvert");
+    cmd.args(["request", "100", "USD", "--address", address])
+        .args(["--valid-for", "18446744073709551615", "--rate", "USD=65000"])
+        .assert()
+        .failure()
+        .code(70)
+        .stderr("A quote can't be valid for 18446744073709551615 minutes\n");
+}
+
+#[test]
+fn test_fee() {
+    let mut cmd = cargo::cargo_bin_cmd!("bitcoinvert");
+    let output = cmd
+        .args(["fee", "--fee-rate", "25", "--vbytes", "141"])
+        .args([
+            "--currency",
+            "sat",
+            "--currency",
+            "usd",
+            "--rates-file",
+            RATES_FILE,
+        ])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.contains("Fee at 25 sat/vB: 3525 SAT"));
+    assert!(output.contains("USD  | 2.29"));
+
+    // 2-in/2-out taproot spend
+    let mut cmd = cargo::cargo_bin_cmd!("bitcoinvert");
+    let output = cmd
+        .args(["fee", "--fee-rate", "40", "--inputs", "2", "--outputs", "2"])
+        .args(["--script", "p2tr", "--currency", "sat"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.contains("2 P2TR input(s) and 2 P2TR output(s): 212 vB"));
+    assert!(output.contains("Fee at 40 sat/vB: 8480 SAT"));
+
+    let mut cmd = cargo::cargo_bin_cmd!("bitcoinvert");
+    cmd.args([
+        "fee",
+        "--fee-rate",
+        "40",
+        "--vbytes",
+        "141",
+        "--inputs",
+        "2",
+    ])
+    .assert()
+    .failure()
+    .code(2);
+}
+
+#[test]
+fn test_fee_with_invalid_transaction() {
+    let invalid: [&[&str]; 6] = [
+        &["--fee-rate", "0", "--vbytes", "141"],
+        &["--fee-rate=-5", "--vbytes", "141"],
+        &["--fee-rate", "NaN", "--vbytes", "141"],
+        &["--fee-rate", "25", "--vbytes", "0"],
+        &["--fee-rate", "25", "--inputs", "0"],
+        &["--fee-rate", "25", "--outputs", "0"],
+    ];
+    for args in invalid {
+        let mut cmd = cargo::cargo_bin_cmd!("bitcoinvert");
+        cmd.arg("fee").args(args).assert().failure().code(2);
+    }
+}
+
+#[test]
+fn test_fee_from_node() {
+    let server = tiny_http::Server::http("127.0.0.1:0").unwrap();
+    let node = format!("http://{}", server.server_addr());
+    std::thread::spawn(move || {
+        let request = server.recv().unwrap();
+        let body = r#"{"result":{"feerate":0.0002,"blocks":2},"error":null}"#;
+        request
+            .respond(tiny_http::Response::from_string(body))
+            .unwrap();
+    });
+
+    let mut cmd = cargo::cargo_bin_cmd!("bitcoinvert");
+    let output = cmd
+        .args(["fee", "--node", &node, "--target", "2", "--vbytes", "141"])
+        .args([
+            "--currency",
+            "sat",
+            "--currency",
+            "usd",
+            "--rates-file",
+            RATES_FILE,
+        ])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.contains("Estimated fee rate to confirm within 2 blocks: 20 sat/vB"));
+    assert!(output.contains("Fee at 20 sat/vB: 2820 SAT"));
+}
+
+#[test]
+fn test_lnfee() {
+    let mut cmd = cargo::cargo_bin_cmd!("bitcoinvert");
+    let output = cmd
+        .args([
+            "lnfee", "250k", "SAT", "--base", "1000msat", "--ppm", "350", "--hops", "3",
+        ])
+        .args([
+            "--currency",
+            "sat",
+            "--currency",
+            "usd",
+            "--rates-file",
+            RATES_FILE,
+        ])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let output = String::from_utf8(output).unwrap();
+
+    assert!(output.contains("over 3 hop(s) at 1,000 msat + 350 ppm: 265,591 MSAT"));
+    assert!(output.contains("Effective fee rate: 1062.4 ppm (0.1062%)"));
+    assert!(output.contains("SAT  | 265.59"));
+
+    let mut cmd = cargo::cargo_bin_cmd!("bitcoinvert");
+    cmd.args(["lnfee", "1", "BTC", "--base", "1usd"])
+        .assert()
+        .failure()
+        .code(2);
+
+    let mut cmd = cargo::cargo_bin_cmd!("bitcoinvert");
+    cmd.args(["lnfee", "250k", "SAT", "--ppm", "1000000", "--hops", "64"])
+        .assert()
+        .failure()
+        .code(2);
+
+    let mut cmd = cargo::cargo_bin_cmd!("bitcoinvert");
+    cmd.args(["lnfee", "1", "BTC", "--base", "200000000btc"])
+        .assert()
+        .failure()
+        .code(70)
+        .stderr("The routing fee is too large to calculate\n");
+}
+
+#[test]
+fn test_subsidy() {
+    let mut cmd = cargo::cargo_bin_cmd!("bitcoinvert");
+    let output = cmd
+        .args([
+            "subsidy",
+            "840000",
+            "--currency",
+            "sat",
+            "--currency",
+            "usd",
+        ])
+        .args(["--rates-file", RATES_FILE])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let output = String::from_utf8(output).unwrap();
+
+    assert!(output.starts_with("Block height: 840,000\nBlock reward: 3.125 BTC\n"));
+    assert!(output.contains("Next halving: block 1,050,000, in 210,000 blocks, around 2028-04-"));
+    assert!(output.contains("Issued supply: 19,687,503.125 BTC (93.75% of 21 million)"));
+    assert!(output.contains("SAT  | 312,500,000"));
+
+    let mut cmd = cargo::cargo_bin_cmd!("bitcoinvert");
+    let output = cmd
+        .args(["subsidy", "2024-04-19", "--currency", "sat"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    assert!(String::from_utf8(output)
+        .unwrap()
+        .starts_with("Block height: 839,856 (estimated)\nBlock reward: 6.25 BTC\n"));
+}
+
+#[test]
+fn test_sanity_checks() {
+    // 50M SAT mistyped as 50M BTC is more bitcoin than will ever exist
+    let mut cmd = cargo::cargo_bin_cmd!("bitcoinvert");
+    let stderr = cmd
+        .args(["-c", "50M", "BTC", "SAT"])
+        .assert()
+        .success()
+        .stdout("5000000000000000\n")
+        .get_output()
+        .stderr
+        .clone();
+    assert!(String::from_utf8(stderr)
+        .unwrap()
+        .contains("Warning: 50000000 BTC is more than the 21 million that will ever exist"));
+
+    let mut cmd = cargo::cargo_bin_cmd!("bitcoinvert");
+    cmd.args(["--strict", "1.5", "MSAT", "SAT"])
+        .assert()
+        .failure()
+        .code(65)
+        .stdout("")
+        .stderr("1.5 MSAT is more precise than MSAT supports (0 decimal places)\n");
+
+    let mut cmd = cargo::cargo_bin_cmd!("bitcoinvert");
+    cmd.args(["--strict", "--", "-1", "SAT", "BTC"])
+        .assert()
+        .failure()
+        .code(65);
+
+    let mut cmd = cargo::cargo_bin_cmd!("bitcoinvert");
+    cmd.args(["--strict", "1", "BTC", "SAT"])
+        .assert()
+        .success()
+        .stdout("100,000,000 SAT\n");
+}
+
+#[test]
+fn test_mining() {
+    let mut cmd = cargo::cargo_bin_cmd!("bitcoinvert");
+    let output = cmd
+        .args(["mining", "--hashrate", "200TH", "--power", "3.5kW"])
+        .args([
+            "--electricity",
+            "0.12USD/kWh",
+            "--network-hashrate",
+            "600EH/s",
+        ])
+        .args(["--height", "840000", "--rate", "USD=100000"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let output = String::from_utf8(output).unwrap();
+
+    assert!(output.contains("Block reward at height 840,000: 312,500,000 SAT"));
+    assert!(output.contains("Revenue:     15,000 SAT = 15 USD per day"));
+    assert!(output.contains("Electricity: 10.08 USD per day"));
+    assert!(output.contains("Profit:      4.92 USD per day"));
+    assert!(output.contains("Hashprice:   75,000 SAT = 75 USD per PH/s per day"));
+
+    let mut cmd = cargo::cargo_bin_cmd!("bitcoinvert");
+    cmd.args(["mining", "--hashrate", "200TW", "--power", "3.5kW"])
+        .args([
+            "--electricity",
+            "0.12USD/kWh",
+            "--network-hashrate",
+            "600EH",
+        ])
+        .assert()
+        .failure()
+        .code(2);
+}
+
+#[test]
+fn test_mining_fees_and_power() {
+    for fees in ["0.1btc", "10Msat"] {
+        let mut cmd = cargo::cargo_bin_cmd!("bitcoinvert");
+        let output = cmd
+            .args(["mining", "--hashrate", "200TH", "--power", "3.5kW"])
+            .args([
+                "--electricity",
+                "0.12USD/kWh",
+                "--network-hashrate",
+                "600EH",
+            ])
+            .args(["--height", "840000", "--fees", fees, "--rate", "USD=100000"])
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone();
+
+        assert!(String::from_utf8(output)
+            .unwrap()
+            .contains("Block reward at height 840,000: 322,500,000 SAT"));
+    }
+
+    for power in ["0W", "-3.5kW"] {
+        let mut cmd = cargo::cargo_bin_cmd!("bitcoinvert");
+        cmd.args(["mining", "--hashrate", "200TH"])
+            .arg(format!("--power={power}"))
+            .args([
+                "--electricity",
+                "0.12USD/kWh",
+                "--network-hashrate",
+                "600EH",
+            ])
+            .assert()
+            .failure()
+            .code(2);
+    }
+}
+
+#[test]
+fn test_portfolio() {
+    let holdings = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/holdings.yaml");
+
+    let mut cmd = cargo::cargo_bin_cmd!("bitcoinvert");
+    let output = cmd
+        .args([
+            "portfolio",
+            "--holdings",
+            holdings,
+            "--currency",
+            "btc",
+            "--currency",
+            "usd",
+        ])
+        .args(["--rates-file", RATES_FILE])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let output = String::from_utf8(output).unwrap();
+    let rows: Vec<Vec<&str>> = output
+        .lines()
+        .skip(2)
+        .map(|line| line.split('|').map(str::trim).collect())
+        .collect();
+
+    assert_eq!(
+        rows,
+        vec![
+            vec!["cold", "0.8 BTC", "0.8", "52,000", "89.1%"],
+            vec!["ln", "2,100,000 SAT", "0.021", "1,365", "2.3%"],
+            vec!["cash", "5,000 USD", "0.07692308", "5,000", "8.6%"],
+            vec!["total", "", "0.89792308", "58,365", "100.0%"],
+        ]
+    );
+}
+
+#[test]
+fn test_gains() {
+    let ledger = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/ledger.csv");
+
+    let mut cmd = cargo::cargo_bin_cmd!("bitcoinvert");
+    let output = cmd
+        .args(["gains", "--ledger", ledger, "--method", "hifo"])
+        .args(["--rates-file", RATES_FILE])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let output = String::from_utf8(output).unwrap();
+
+    assert!(output.starts_with("Cost basis method: HIFO\n"));
+    assert!(output.contains(" 2023     | 54,000 USD | 52,500 USD | 1,500 USD "));
+    assert!(output.contains(" 2024     | 30,000 USD | 12,500 USD | 17,500 USD "));
+    assert!(output.contains("Cost basis:      20,000 USD\n"));
+    assert!(output.contains("Unrealized gain: 45,000 USD\n"));
+}
+
+#[test]
+fn test_gains_with_historical_prices() {
+    let ledger = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/fixtures/ledger_without_prices.csv"
+    );
+    let prices = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/prices.csv");
+
+    let mut cmd = cargo::cargo_bin_cmd!("bitcoinvert");
+    let output = cmd
+        .args(["gains", "--ledger", ledger, "--method", "hifo"])
+        .args(["--prices", prices, "--rates-file", RATES_FILE])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.contains(" 2024     | 30,000 USD | 12,500 USD | 17,500 USD "));
+
+    // Only USD prices can be fetched
+    let mut cmd = cargo::cargo_bin_cmd!("bitcoinvert");
+    cmd.args(["gains", "--ledger", ledger, "--currency", "EUR"])
+        .args(["--rates-file", RATES_FILE])
+        .assert()
+        .failure()
+        .code(70)
+        .stderr(
+            "Historical prices are only available in USD. Add the missing prices in EUR to the \
+             ledger, or list them with --prices FILE.\n",
+        );
+}
+
+#[test]
+fn test_currency_without_rate() {
+    let mut cmd = cargo::cargo_bin_cmd!("bitcoinvert");
+    let output = cmd
+        .args(["--rates-file", RATES_FILE, "1", "BTC", "MXN"])
+        .assert()
+        .failure()
+        .code(69)
+        .get_output()
+        .stderr
+        .clone();
+
+    assert!(String::from_utf8(output)
+        .unwrap()
+        .starts_with("No exchange rate available for MXN."));
+}
+
+/// Runs a subcommand that needs the MXN rate, which the rates file lacks.
+fn assert_missing_mxn_rate(args: &[&str]) {
+    let mut cmd = cargo::cargo_bin_cmd!("bitcoinvert");
+    let output = cmd
+        .args(args)
+        .args(["--rates-file", RATES_FILE])
+        .assert()
+        .failure()
+        .code(69)
+        .get_output()
+        .stderr
+        .clone();
+
+    assert!(String::from_utf8(output)
+        .unwrap()
+        .starts_with("No exchange rate available for MXN."));
+}
+
+#[test]
+fn test_fee_without_rate() {
+    assert_missing_mxn_rate(&[
+        "fee",
+        "--fee-rate",
+        "25",
+        "--vbytes",
+        "141",
+        "--currency",
+        "mxn",
+    ]);
+}
+
+#[test]
+fn test_lnfee_without_rate() {
+    assert_missing_mxn_rate(&["lnfee", "1000", "MXN", "--currency", "sat"]);
+    assert_missing_mxn_rate(&["lnfee", "1000", "SAT", "--currency", "mxn"]);
+}
+
+#[test]
+fn test_mining_without_rate() {
+    assert_missing_mxn_rate(&[
+        "mining",
+        "--hashrate",
+        "200TH",
+        "--power",
+        "3.5kW",
+        "--electricity",
+        "2MXN/kWh",
+        "--network-hashrate",
+        "600EH",
+        "--height",
+        "840000",
+    ]);
+}
+
+#[test]
+fn test_subsidy_without_rate() {
+    assert_missing_mxn_rate(&["subsidy", "840000", "--currency", "mxn"]);
+}
+
+#[test]
+fn test_portfolio_without_rate() {
+    let holdings = std::env::temp_dir().join("bitcoinvert_mxn_holdings.yaml");
+    std::fs::write(&holdings, "cold: 0.8 BTC\ncash: 5000 MXN\n").unwrap();
+
+    assert_missing_mxn_rate(&[
+        "portfolio",
+        "--holdings",
+        holdings.to_str().unwrap(),
+        "--currency",
+        "btc",
+    ]);
+}
+
+#[test]
+fn test_gains_without_rate() {
+    let ledger = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/ledger.csv");
+
+    assert_missing_mxn_rate(&["gains", "--ledger", ledger, "--currency", "MXN"]);
+}
+
+#[test]
+fn test_request_without_rate() {
+    assert_missing_mxn_rate(&[
+        "request",
+        "100",
+        "MXN",
+        "--address",
+        "bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq",
+    ]);
+}
+
+#[test]
+fn test_bitcoin_units_without_defaults() {
+    // A broken configuration doesn't matter as long as no exchange rate is needed
+    let home = std::env::temp_dir().join("bitcoinvert_broken_home");
+    let config_dir = home.join(".config").join("bitcoinvert");
+    std::fs::create_dir_all(&config_dir).unwrap();
+    std::fs::write(config_dir.join("defaults.yaml"), "rates_file: [broken").unwrap();
+
+    let mut cmd = cargo::cargo_bin_cmd!("bitcoinvert");
+    cmd.env("HOME", &home)
+        .args(["1", "BTC", "SAT"])
+        .assert()
+        .success()
+        .stdout("100,000,000 SAT\n");
+}
+
+#[test]
+fn test_serve_custom_unit() {
+    let home = std::env::temp_dir().join("bitcoinvert_custom_unit_home");
+    let config_dir = home.join(".config").join("bitcoinvert");
+    std::fs::create_dir_all(&config_dir).unwrap();
+    std::fs::write(
+        config_dir.join("defaults.yaml"),
+        "amount: 1.0\ninput_currency:\n  CustomUnit: coffee\noutput_currencies:\n  - BitcoinUnit: SAT\ncustom_units:\n  coffee: 6.50 USD\n",
+    )
+    .unwrap();
+
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let listen = format!("127.0.0.1:{port}");
+    let mut server = std::process::Command::new(cargo::cargo_bin!("bitcoinvert"))
+        .env("HOME", &home)
+        .args(["serve", "--rates-file", RATES_FILE, "--listen", &listen])
+        .stderr(std::process::Stdio::null())
+        .spawn()
+        .unwrap();
+
+    // Custom units from the defaults are as valid as the built-in currencies
+    let url = format!("http://{listen}/convert?amount=10&from=coffee&to=sat");
+    let response = (0..50).find_map(|_| {
+        std::thread::sleep(std::time::Duration::from_millis(100));
+        reqwest::blocking::get(&url).ok()
+    });
+    server.kill().unwrap();
+    server.wait().unwrap();
+
+    let body: serde_json::Value = response.unwrap().json().unwrap();
+    assert_eq!(body["result"], serde_json::json!(100_000.0));
+}
+
 #[test]
 fn test_clean_mode() {
     let mut cmd = cargo::cargo_bin_cmd!("bitcoinvert");
@@ -121,7 +1062,14 @@ fn test_amount_output_rounding() {
 
     let mut cmd = cargo::cargo_bin_cmd!("bitcoinvert");
     let stdout = cmd
-        .args(vec!["-c", "0.123", "BTC", "USD"])
+        .args(vec![
+            "--rates-file",
+            RATES_FILE,
+            "-c",
+            "0.123",
+            "BTC",
+            "USD",
+        ])
         .assert()
         .get_output()
         .stdout
@@ -142,7 +1090,7 @@ fn test_amount_output_rounding() {
 
     let mut cmd = cargo::cargo_bin_cmd!("bitcoinvert");
     let stdout = cmd
-        .args(vec!["-c", "21", "BTC", "JPY"])
+        .args(vec!["--rates-file", RATES_FILE, "-c", "21", "BTC", "JPY"])
         .assert()
         .get_output()
         .stdout
@@ -163,7 +1111,12 @@ fn test_amount_output_rounding() {
 fn test_format() {
     let mut cmd = cargo::cargo_bin_cmd!("bitcoinvert");
 
-    let stdout = cmd.arg("-i").assert().get_output().stdout.clone();
+    let stdout = cmd
+        .args(["--rates-file", RATES_FILE, "-i"])
+        .assert()
+        .get_output()
+        .stdout
+        .clone();
     let stdout = String::from_utf8(stdout).unwrap();
     let stdout_lines: Vec<_> = stdout.split('\n').collect();
 